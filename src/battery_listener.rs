@@ -1,3 +1,4 @@
+use crate::window_manager::{EventHub, HubEvent};
 use async_channel;
 use std::fs;
 use std::os::unix::io::AsRawFd;
@@ -24,33 +25,32 @@ fn read_battery_info() -> Option<BatteryInfo> {
     Some(BatteryInfo { capacity, status })
 }
 
-fn send_if_changed(
-    sender: &async_channel::Sender<BatteryInfo>,
-    last: &Arc<Mutex<Option<BatteryInfo>>>,
-) -> bool {
+impl HubEvent for BatteryInfo {
+    fn connection(_connected: bool) -> Option<Self> {
+        None
+    }
+}
+
+static EVENTS: EventHub<BatteryInfo> = EventHub::new();
+static CURRENT: Mutex<Option<BatteryInfo>> = Mutex::new(None);
+
+fn publish_if_changed() {
     if let Some(info) = read_battery_info() {
-        let mut guard = last.lock().unwrap();
-        if guard.as_ref() != Some(&info) {
-            let _ = sender.send_blocking(info.clone());
-            *guard = Some(info);
-            return true;
+        let mut current = CURRENT.lock().unwrap();
+        if current.as_ref() != Some(&info) {
+            *current = Some(info.clone());
+            EVENTS.broadcast(info);
         }
     }
-    false
 }
 
-pub fn start_battery_listener() -> async_channel::Receiver<BatteryInfo> {
-    let (sender, receiver) = async_channel::unbounded();
-    let last_state: Arc<Mutex<Option<BatteryInfo>>> = Arc::new(Mutex::new(None));
-
-    send_if_changed(&sender, &last_state);
+fn run_event_stream() {
+    publish_if_changed();
 
     let pending_since: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
 
     {
         let pending_since = Arc::clone(&pending_since);
-        let sender = sender.clone();
-        let last_state = Arc::clone(&last_state);
         std::thread::spawn(move || {
             loop {
                 std::thread::sleep(Duration::from_millis(100));
@@ -63,7 +63,7 @@ pub fn start_battery_listener() -> async_channel::Receiver<BatteryInfo> {
                         let mut guard = pending_since.lock().unwrap();
                         *guard = None;
                     }
-                    send_if_changed(&sender, &last_state);
+                    publish_if_changed();
                 }
             }
         });
@@ -111,14 +111,16 @@ pub fn start_battery_listener() -> async_channel::Receiver<BatteryInfo> {
         });
     }
 
-    {
-        let sender = sender.clone();
-        let last_state = Arc::clone(&last_state);
-        std::thread::spawn(move || loop {
-            std::thread::sleep(Duration::from_secs(30));
-            send_if_changed(&sender, &last_state);
-        });
+    loop {
+        std::thread::sleep(Duration::from_secs(30));
+        publish_if_changed();
     }
+}
 
-    receiver
-}
\ No newline at end of file
+/// Every listener shares one udev monitor, and starts from the current state
+/// once it's known.
+pub fn start_battery_listener() -> async_channel::Receiver<BatteryInfo> {
+    // Held while subscribing so no change slips in between.
+    let current = CURRENT.lock().unwrap();
+    EVENTS.subscribe_from(run_event_stream, current.clone())
+}
@@ -12,10 +12,12 @@ const NOB_GAP: f64 = 1.0;
 const TOTAL_W: f64 = BODY_W + NOB_GAP + NOB_W;
 const RADIUS: f64 = 8.0;
 
-pub fn build_battery_widget() -> (GBox, impl Fn(BatteryInfo)) {
-    let container = GBox::new(Orientation::Horizontal, 0);
+pub fn build_battery_widget(orientation: Orientation) -> (GBox, impl Fn(BatteryInfo)) {
+    let vertical = orientation == Orientation::Vertical;
+    let container = GBox::new(orientation, 0);
     container.add_css_class("battery-box");
     container.set_valign(gtk4::Align::Center);
+    container.set_halign(gtk4::Align::Center);
 
     let capacity: Rc<Cell<u8>> = Rc::new(Cell::new(100));
     let is_charging: Rc<Cell<bool>> = Rc::new(Cell::new(false));
@@ -23,8 +25,13 @@ pub fn build_battery_widget() -> (GBox, impl Fn(BatteryInfo)) {
     let is_low: Rc<Cell<bool>> = Rc::new(Cell::new(false));
 
     let area = DrawingArea::new();
-    area.set_content_width(TOTAL_W as i32);
-    area.set_content_height(BODY_H as i32);
+    if vertical {
+        area.set_content_width(BODY_H as i32);
+        area.set_content_height(TOTAL_W as i32);
+    } else {
+        area.set_content_width(TOTAL_W as i32);
+        area.set_content_height(BODY_H as i32);
+    }
     area.set_valign(gtk4::Align::Center);
 
     {
@@ -34,6 +41,11 @@ pub fn build_battery_widget() -> (GBox, impl Fn(BatteryInfo)) {
         let is_low = is_low.clone();
 
         area.set_draw_func(move |_area, cr, _width, _height| {
+            if vertical {
+                // Stand the battery upright with the nob pointing up.
+                cr.translate(0.0, TOTAL_W);
+                cr.rotate(-std::f64::consts::FRAC_PI_2);
+            }
             draw_battery(
                 cr,
                 capacity.get(),
@@ -1,6 +1,8 @@
+use crate::window_manager::{EventHub, HubEvent};
 use async_channel;
-use std::io::{BufRead, BufReader, Write};
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BluetoothInfo {
@@ -50,24 +52,35 @@ fn get_bluetooth_info() -> BluetoothInfo {
     }
 }
 
-pub fn start_bluetooth_listener() -> async_channel::Receiver<BluetoothInfo> {
-    let (sender, receiver) = async_channel::unbounded();
+impl HubEvent for BluetoothInfo {
+    fn connection(_connected: bool) -> Option<Self> {
+        None
+    }
+}
 
-    let _ = sender.send_blocking(get_bluetooth_info());
+static EVENTS: EventHub<BluetoothInfo> = EventHub::new();
+static CURRENT: Mutex<Option<BluetoothInfo>> = Mutex::new(None);
 
-    std::thread::spawn(move || {
-        let mut child = match Command::new("bluetoothctl")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-        {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("[bluetooth] Failed to spawn bluetoothctl: {}", e);
-                return;
-            }
-        };
+fn publish(info: BluetoothInfo) {
+    let mut current = CURRENT.lock().unwrap();
+    if current.as_ref() != Some(&info) {
+        *current = Some(info.clone());
+        EVENTS.broadcast(info);
+    }
+}
+
+fn spawn_bluetoothctl() -> io::Result<Child> {
+    Command::new("bluetoothctl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+fn run_event_stream() {
+    EVENTS.run_with_backoff("bluetooth", spawn_bluetoothctl, |mut child| {
+        // Anything may have changed while bluetoothctl wasn't running.
+        publish(get_bluetooth_info());
 
         let mut stdin = child.stdin.take().expect("bluetoothctl has no stdin");
         std::thread::spawn(move || {
@@ -80,20 +93,19 @@ pub fn start_bluetooth_listener() -> async_channel::Receiver<BluetoothInfo> {
         });
 
         let stdout = child.stdout.take().expect("bluetoothctl has no stdout");
-        let reader = BufReader::new(stdout);
-
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if line.contains("[CHG]")
-                    && (line.contains("Connected") || line.contains("Powered"))
-                {
-                    let _ = sender.send_blocking(get_bluetooth_info());
-                }
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line.contains("[CHG]") && (line.contains("Connected") || line.contains("Powered")) {
+                publish(get_bluetooth_info());
             }
         }
-
         let _ = child.wait();
-    });
+    })
+}
 
-    receiver
-}
\ No newline at end of file
+/// Every listener shares one `bluetoothctl`, and starts from the current
+/// state once it's known.
+pub fn start_bluetooth_listener() -> async_channel::Receiver<BluetoothInfo> {
+    // Held while subscribing so no change slips in between.
+    let current = CURRENT.lock().unwrap();
+    EVENTS.subscribe_from(run_event_stream, current.clone())
+}
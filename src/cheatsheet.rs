@@ -0,0 +1,188 @@
+use crate::hyprland_ipc::{self, Bind};
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{
+    Application, ApplicationWindow, Box as GBox, EventControllerKey, Grid, Label, Orientation,
+    PolicyType, ScrolledWindow, SearchEntry,
+};
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+const WIDTH: i32 = 720;
+const HEIGHT: i32 = 560;
+
+// Hyprland's modmask bits, in the order they're usually written.
+const MODIFIERS: [(u32, &str); 8] = [
+    (64, "SUPER"),
+    (4, "CTRL"),
+    (8, "ALT"),
+    (1, "SHIFT"),
+    (2, "CAPS"),
+    (16, "MOD2"),
+    (32, "MOD3"),
+    (128, "MOD5"),
+];
+
+fn key_combo(bind: &Bind) -> String {
+    let mut parts: Vec<&str> = MODIFIERS
+        .iter()
+        .filter(|(bit, _)| bind.modmask & bit != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    parts.push(&bind.key);
+    let combo = parts.join(" + ");
+    if bind.submap.is_empty() {
+        combo
+    } else {
+        format!("[{}] {}", bind.submap, combo)
+    }
+}
+
+fn action_text(bind: &Bind) -> &str {
+    if !bind.description.is_empty() {
+        &bind.description
+    } else {
+        &bind.arg
+    }
+}
+
+fn matches(bind: &Bind, query: &str) -> bool {
+    query.is_empty()
+        || [
+            key_combo(bind),
+            bind.dispatcher.clone(),
+            bind.arg.clone(),
+            bind.description.clone(),
+        ]
+        .iter()
+        .any(|text| text.to_lowercase().contains(query))
+}
+
+fn render(list: &GBox, binds: &[Bind], query: &str) {
+    while let Some(child) = list.first_child() {
+        list.remove(&child);
+    }
+
+    let query = query.trim().to_lowercase();
+    let mut groups: BTreeMap<&str, Vec<&Bind>> = BTreeMap::new();
+    for bind in binds.iter().filter(|b| matches(b, &query)) {
+        groups.entry(&bind.dispatcher).or_default().push(bind);
+    }
+
+    if groups.is_empty() {
+        let empty = Label::new(Some("No matching keybindings"));
+        empty.add_css_class("dim-label");
+        list.append(&empty);
+        return;
+    }
+
+    for (dispatcher, binds) in groups {
+        let heading = Label::new(Some(dispatcher));
+        heading.add_css_class("heading");
+        heading.set_halign(gtk4::Align::Start);
+        list.append(&heading);
+
+        let grid = Grid::new();
+        grid.set_column_spacing(16);
+        grid.set_row_spacing(2);
+        for (row, bind) in binds.iter().enumerate() {
+            let keys = Label::new(Some(&key_combo(bind)));
+            keys.add_css_class("keys");
+            keys.set_halign(gtk4::Align::Start);
+            let action = Label::new(Some(action_text(bind)));
+            action.set_halign(gtk4::Align::Start);
+            action.set_wrap(true);
+            grid.attach(&keys, 0, row as i32, 1, 1);
+            grid.attach(&action, 1, row as i32, 1, 1);
+        }
+        list.append(&grid);
+    }
+}
+
+/// Builds the hidden overlay and returns a function that toggles it. The
+/// binds are fetched again every time it opens, so edits to the Hyprland
+/// config show up without restarting the panel.
+pub fn build_cheatsheet(app: &Application) -> impl Fn() + 'static {
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .decorated(false)
+        .default_width(WIDTH)
+        .default_height(HEIGHT)
+        .build();
+    window.init_layer_shell();
+    window.set_namespace(Some("hypr-panel-cheatsheet"));
+    window.set_layer(Layer::Overlay);
+    // The filter box needs the keyboard; with no anchors the window is centered.
+    window.set_keyboard_mode(KeyboardMode::Exclusive);
+
+    let content = GBox::new(Orientation::Vertical, 8);
+    content.add_css_class("cheatsheet");
+
+    let entry = SearchEntry::new();
+    entry.set_placeholder_text(Some("Filter keybindings"));
+    content.append(&entry);
+
+    let list = GBox::new(Orientation::Vertical, 6);
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(PolicyType::Never, PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&list));
+    content.append(&scrolled);
+
+    window.set_child(Some(&content));
+    window.set_visible(false);
+
+    let binds: Rc<RefCell<Vec<Bind>>> = Rc::new(RefCell::new(Vec::new()));
+
+    {
+        let binds = binds.clone();
+        let list = list.clone();
+        entry.connect_search_changed(move |entry| {
+            render(&list, &binds.borrow(), &entry.text());
+        });
+    }
+
+    let keys = EventControllerKey::new();
+    {
+        let window = window.clone();
+        keys.connect_key_pressed(move |_controller, key, _code, _state| {
+            if key == gtk4::gdk::Key::Escape {
+                window.set_visible(false);
+                glib::Propagation::Stop
+            } else {
+                glib::Propagation::Proceed
+            }
+        });
+    }
+    window.add_controller(keys);
+
+    move || {
+        if window.is_visible() {
+            window.set_visible(false);
+            return;
+        }
+
+        entry.set_text("");
+        window.set_visible(true);
+        entry.grab_focus();
+
+        let (sender, receiver) = async_channel::bounded(1);
+        std::thread::spawn(move || {
+            let _ = sender.send_blocking(hyprland_ipc::binds());
+        });
+
+        let binds = binds.clone();
+        let list = list.clone();
+        let entry = entry.clone();
+        glib::spawn_future_local(async move {
+            match receiver.recv().await {
+                Ok(Ok(fetched)) => *binds.borrow_mut() = fetched,
+                Ok(Err(e)) => eprintln!("[cheatsheet] Failed to fetch binds: {}", e),
+                Err(_) => return,
+            }
+            render(&list, &binds.borrow(), &entry.text());
+        });
+    }
+}
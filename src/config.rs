@@ -0,0 +1,254 @@
+use crate::paths;
+use std::fs;
+
+const DEFAULT_CONFIG: &str = include_str!("../config.toml");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleConfig {
+    pub name: String,
+    pub options: toml::Table,
+}
+
+impl ModuleConfig {
+    pub fn str(&self, key: &str) -> Option<&str> {
+        self.options.get(key)?.as_str()
+    }
+
+    pub fn int(&self, key: &str) -> Option<i64> {
+        self.options.get(key)?.as_integer()
+    }
+
+    pub fn bool(&self, key: &str) -> Option<bool> {
+        self.options.get(key)?.as_bool()
+    }
+
+    pub fn table(&self, key: &str) -> Option<&toml::Table> {
+        self.options.get(key)?.as_table()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Position {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Position {
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Position::Left | Position::Right)
+    }
+}
+
+/// What a panel does while its monitor shows a fullscreen window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FullscreenMode {
+    Hide,
+    Lower,
+    Ignore,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub position: Position,
+    pub size: i32,
+    pub margin: i32,
+    pub spacing: i32,
+    pub fullscreen: FullscreenMode,
+    pub fullscreen_hud: bool,
+    pub start: Vec<ModuleConfig>,
+    pub center: Vec<ModuleConfig>,
+    pub end: Vec<ModuleConfig>,
+}
+
+fn parse_modules(value: Option<&toml::Value>) -> Vec<ModuleConfig> {
+    let Some(entries) = value.and_then(|v| v.as_array()) else {
+        return vec![];
+    };
+
+    entries
+        .iter()
+        .filter_map(|entry| match entry {
+            toml::Value::String(name) => Some(ModuleConfig {
+                name: name.clone(),
+                options: toml::Table::new(),
+            }),
+            toml::Value::Table(table) => {
+                let mut options = table.clone();
+                match options.remove("module") {
+                    Some(toml::Value::String(name)) => Some(ModuleConfig { name, options }),
+                    _ => {
+                        eprintln!("[config] Module entry without a `module` name: {}", table);
+                        None
+                    }
+                }
+            }
+            other => {
+                eprintln!("[config] Ignoring invalid module entry: {}", other);
+                None
+            }
+        })
+        .collect()
+}
+
+fn parse_config(text: &str) -> Result<Config, toml::de::Error> {
+    let table: toml::Table = text.parse()?;
+    let panel = table.get("panel").and_then(|v| v.as_table());
+    let panel_int = |key: &str, default: i32| {
+        panel
+            .and_then(|p| p.get(key))
+            .and_then(|v| v.as_integer())
+            .map_or(default, |v| v as i32)
+    };
+
+    let position = match panel
+        .and_then(|p| p.get("position"))
+        .and_then(|v| v.as_str())
+    {
+        None | Some("top") => Position::Top,
+        Some("bottom") => Position::Bottom,
+        Some("left") => Position::Left,
+        Some("right") => Position::Right,
+        Some(other) => {
+            eprintln!("[config] Unknown panel position {:?}, using top", other);
+            Position::Top
+        }
+    };
+
+    let fullscreen = match panel
+        .and_then(|p| p.get("fullscreen"))
+        .and_then(|v| v.as_str())
+    {
+        None | Some("hide") => FullscreenMode::Hide,
+        Some("lower") => FullscreenMode::Lower,
+        Some("ignore") => FullscreenMode::Ignore,
+        Some(other) => {
+            eprintln!("[config] Unknown fullscreen mode {:?}, using hide", other);
+            FullscreenMode::Hide
+        }
+    };
+
+    Ok(Config {
+        position,
+        // `height` predates vertical panels and is still accepted.
+        size: panel_int("size", panel_int("height", 25)),
+        margin: panel_int("margin", 7),
+        spacing: panel_int("spacing", 8),
+        fullscreen,
+        fullscreen_hud: panel
+            .and_then(|p| p.get("fullscreen_hud"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true),
+        start: parse_modules(table.get("start")),
+        center: parse_modules(table.get("center")),
+        end: parse_modules(table.get("end")),
+    })
+}
+
+pub fn load_config() -> Config {
+    if let Some(path) = paths::find_file("config.toml") {
+        match fs::read_to_string(&path) {
+            Ok(text) => match parse_config(&text) {
+                Ok(config) => return config,
+                Err(e) => eprintln!("[config] Failed to parse {}: {}", path.display(), e),
+            },
+            Err(e) => eprintln!("[config] Failed to read {}: {}", path.display(), e),
+        }
+    }
+
+    parse_config(DEFAULT_CONFIG).expect("built-in config.toml is invalid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_built_in_config() {
+        let config = parse_config(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config.position, Position::Top);
+        assert!(!config.start.is_empty());
+        assert!(config.center.iter().any(|m| m.name == "workspaces"));
+    }
+
+    #[test]
+    fn falls_back_to_defaults() {
+        let config = parse_config("").unwrap();
+        assert_eq!(config.position, Position::Top);
+        assert_eq!(config.size, 25);
+        assert_eq!(config.margin, 7);
+        assert_eq!(config.spacing, 8);
+        assert_eq!(config.fullscreen, FullscreenMode::Hide);
+        assert!(config.fullscreen_hud);
+        assert!(config.start.is_empty() && config.center.is_empty() && config.end.is_empty());
+    }
+
+    #[test]
+    fn reads_panel_options() {
+        let config = parse_config(
+            r#"
+            [panel]
+            position = "left"
+            size = 40
+            margin = 2
+            fullscreen = "lower"
+            fullscreen_hud = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.position, Position::Left);
+        assert!(config.position.is_vertical());
+        assert_eq!(config.size, 40);
+        assert_eq!(config.margin, 2);
+        assert_eq!(config.fullscreen, FullscreenMode::Lower);
+        assert!(!config.fullscreen_hud);
+    }
+
+    #[test]
+    fn accepts_height_and_unknown_values() {
+        let config = parse_config(
+            r#"
+            [panel]
+            position = "diagonal"
+            height = 30
+            fullscreen = "sometimes"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.position, Position::Top);
+        assert_eq!(config.size, 30);
+        assert_eq!(config.fullscreen, FullscreenMode::Hide);
+    }
+
+    #[test]
+    fn parses_module_entries() {
+        let config = parse_config(
+            r#"
+            start = ["logo"]
+
+            [[end]]
+            module = "clock"
+            format = "%H:%M"
+
+            [[end]]
+            format = "no module name"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.start.len(), 1);
+        assert_eq!(config.start[0].name, "logo");
+        assert!(config.start[0].options.is_empty());
+
+        // The entry without a name is dropped.
+        assert_eq!(config.end.len(), 1);
+        assert_eq!(config.end[0].name, "clock");
+        assert_eq!(config.end[0].str("format"), Some("%H:%M"));
+        assert!(!config.end[0].options.contains_key("module"));
+    }
+
+    #[test]
+    fn rejects_invalid_toml() {
+        assert!(parse_config("[panel").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::paths;
+use inotify::{Inotify, WatchMask};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigChange {
+    Style,
+    Config,
+    Logo,
+}
+
+fn classify(name: &std::ffi::OsStr) -> Option<ConfigChange> {
+    match name.to_str()? {
+        "style.css" => Some(ConfigChange::Style),
+        "config.toml" => Some(ConfigChange::Config),
+        // The default logo next to the config.
+        "logo.svg" => Some(ConfigChange::Logo),
+        _ => None,
+    }
+}
+
+pub fn start_config_listener() -> async_channel::Receiver<ConfigChange> {
+    let (sender, receiver) = async_channel::unbounded();
+
+    std::thread::spawn(move || {
+        let mut inotify = match Inotify::init() {
+            Ok(i) => i,
+            Err(e) => {
+                eprintln!("[config] Failed to initialise inotify: {}", e);
+                return;
+            }
+        };
+
+        // Watch directories rather than files so editors that save by
+        // renaming a temp file over the original are still noticed.
+        let mask =
+            WatchMask::CLOSE_WRITE | WatchMask::MOVED_TO | WatchMask::CREATE | WatchMask::DELETE;
+        let mut watching = false;
+        for dir in paths::search_dirs().into_iter().filter(|d| d.is_dir()) {
+            match inotify.watches().add(&dir, mask) {
+                Ok(_) => watching = true,
+                Err(e) => eprintln!("[config] Failed to watch {}: {}", dir.display(), e),
+            }
+        }
+        if !watching {
+            eprintln!("[config] No config directory to watch, live reload disabled");
+            return;
+        }
+
+        let mut buffer = [0u8; 4096];
+        loop {
+            let mut changes = Vec::new();
+            match inotify.read_events_blocking(&mut buffer) {
+                Ok(events) => changes.extend(events.filter_map(|e| e.name.and_then(classify))),
+                Err(e) => {
+                    eprintln!("[config] inotify read error: {}", e);
+                    return;
+                }
+            }
+
+            // Editors tend to write a file in several steps; let them settle
+            // and collapse the burst into one reload per kind of change.
+            std::thread::sleep(Duration::from_millis(150));
+            if let Ok(events) = inotify.read_events(&mut buffer) {
+                changes.extend(events.filter_map(|e| e.name.and_then(classify)));
+            }
+
+            for change in [
+                ConfigChange::Style,
+                ConfigChange::Config,
+                ConfigChange::Logo,
+            ] {
+                if changes.contains(&change) && sender.send_blocking(change).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    receiver
+}
@@ -0,0 +1,155 @@
+use crate::paths;
+use gtk4::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopEntry {
+    pub id: String,
+    pub name: String,
+    pub icon: Option<String>,
+    pub startup_wm_class: Option<String>,
+}
+
+fn parse_entry(id: String, text: &str) -> Option<DesktopEntry> {
+    let mut in_main_section = false;
+    let mut entry = DesktopEntry {
+        id,
+        name: String::new(),
+        icon: None,
+        startup_wm_class: None,
+    };
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_main_section = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_main_section {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key.trim() {
+            "Name" => entry.name = value,
+            "Icon" => entry.icon = Some(value),
+            "StartupWMClass" => entry.startup_wm_class = Some(value),
+            "Hidden" if value == "true" => return None,
+            _ => {}
+        }
+    }
+
+    Some(entry)
+}
+
+// Desktop file ids of entries in subdirectories join the path with dashes,
+// e.g. `kde/org.kde.dolphin.desktop` becomes `kde-org.kde.dolphin`.
+fn scan_dir(root: &Path, dir: &Path, entries: &mut Vec<DesktopEntry>) {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in read_dir.flatten().map(|e| e.path()) {
+        if path.is_dir() {
+            scan_dir(root, &path, entries);
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "desktop") {
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let id = relative
+            .with_extension("")
+            .to_string_lossy()
+            .replace('/', "-");
+        // Earlier data dirs take precedence over later ones.
+        if entries.iter().any(|e| e.id == id) {
+            continue;
+        }
+        if let Some(entry) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| parse_entry(id, &text))
+        {
+            entries.push(entry);
+        }
+    }
+}
+
+fn entries() -> &'static [DesktopEntry] {
+    static ENTRIES: OnceLock<Vec<DesktopEntry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        let mut entries = Vec::new();
+        for dir in paths::xdg_data_dirs() {
+            let applications = dir.join("applications");
+            scan_dir(&applications, &applications, &mut entries);
+        }
+        entries
+    })
+}
+
+pub fn find_by_class(class: &str) -> Option<&'static DesktopEntry> {
+    if class.is_empty() {
+        return None;
+    }
+    let entries = entries();
+
+    entries
+        .iter()
+        .find(|e| {
+            e.startup_wm_class
+                .as_deref()
+                .is_some_and(|wm_class| wm_class.eq_ignore_ascii_case(class))
+        })
+        .or_else(|| entries.iter().find(|e| e.id.eq_ignore_ascii_case(class)))
+        // Reverse-DNS ids such as `org.mozilla.firefox` for class `firefox`.
+        .or_else(|| {
+            entries.iter().find(|e| {
+                e.id.rsplit('.')
+                    .next()
+                    .is_some_and(|last| last.eq_ignore_ascii_case(class))
+            })
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppIcon {
+    Name(String),
+    File(PathBuf),
+}
+
+pub fn icon_for_class(class: &str) -> Option<AppIcon> {
+    let icon = match find_by_class(class).and_then(|e| e.icon.clone()) {
+        Some(icon) => icon,
+        // Many apps use their class as the icon name without shipping a
+        // matching desktop file.
+        None if !class.is_empty() => class.to_lowercase(),
+        None => return None,
+    };
+
+    if Path::new(&icon).is_absolute() {
+        Some(AppIcon::File(PathBuf::from(icon)))
+    } else {
+        Some(AppIcon::Name(icon))
+    }
+}
+
+// Returns false when no icon could be found for the class.
+pub fn set_app_icon(image: &gtk4::Image, class: &str) -> bool {
+    match icon_for_class(class) {
+        Some(AppIcon::File(path)) if path.is_file() => {
+            image.set_from_file(Some(path));
+            true
+        }
+        Some(AppIcon::Name(name))
+            if gtk4::IconTheme::for_display(&image.display()).has_icon(&name) =>
+        {
+            image.set_icon_name(Some(&name));
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,140 @@
+use crate::hyprland_events::{self, HyprEvent};
+use crate::hyprland_ipc;
+use crate::window_manager::{
+    Action, ActiveWindowInfo, WindowManager, WmEvent, Workspace, WorkspaceInfo,
+};
+use std::io;
+
+fn to_workspace(ws: &hyprland_ipc::Workspace) -> Workspace {
+    // Special workspaces are named `special:name`, or just `special`.
+    let name = match ws.name.strip_prefix("special") {
+        Some(name) => name.trim_start_matches(':'),
+        None => &ws.name,
+    };
+    Workspace {
+        id: ws.id,
+        name: name.to_string(),
+        windows: ws.windows,
+    }
+}
+
+fn workspace_of_window(address: &str) -> Option<i32> {
+    let clients = hyprland_ipc::clients().ok()?;
+    clients
+        .iter()
+        .find(|c| c.address == address)
+        .map(|c| c.workspace.id)
+}
+
+pub struct Hyprland;
+
+impl WindowManager for Hyprland {
+    fn name(&self) -> &'static str {
+        "Hyprland"
+    }
+
+    fn workspaces(&self, monitor: Option<&str>) -> io::Result<WorkspaceInfo> {
+        let replies = hyprland_ipc::batch_json(&["monitors", "activeworkspace", "workspaces"])?;
+        let monitors = hyprland_ipc::parse_monitors(&replies[0]);
+        let workspaces = hyprland_ipc::parse_workspaces(&replies[2]);
+        let mut info = WorkspaceInfo::default();
+
+        // A panel bound to a monitor shows that monitor's active workspace,
+        // which is not necessarily the focused one.
+        let bound = monitor.and_then(|name| monitors.iter().find(|m| m.name == name));
+        let shown = bound.or_else(|| monitors.iter().find(|m| m.focused));
+
+        let active_id = bound
+            .map(|m| m.active_workspace.id)
+            .or_else(|| replies[1]["id"].as_i64().map(|id| id as i32));
+        if let Some(id) = active_id {
+            info.active = id;
+        }
+
+        info.special = shown
+            .map(|m| m.special_workspace.id)
+            .filter(|id| *id != 0)
+            .and_then(|id| workspaces.iter().find(|ws| ws.id == id))
+            .map(to_workspace);
+
+        info.workspaces = workspaces
+            .iter()
+            .filter(|ws| !ws.is_special())
+            .filter(|ws| bound.is_none_or(|m| ws.monitor == m.name))
+            .map(to_workspace)
+            .collect();
+        info.workspaces.sort_unstable_by_key(|ws| ws.id);
+
+        Ok(info)
+    }
+
+    fn focused_window(&self) -> io::Result<Option<ActiveWindowInfo>> {
+        Ok(
+            hyprland_ipc::active_window()?.map(|window| ActiveWindowInfo {
+                class: window.class,
+                title: window.title,
+            }),
+        )
+    }
+
+    fn subscribe(&self) -> async_channel::Receiver<WmEvent> {
+        let (sender, receiver) = async_channel::unbounded();
+        let events = hyprland_events::subscribe();
+
+        std::thread::spawn(move || {
+            while let Ok(event) = events.recv_blocking() {
+                let event = match event {
+                    HyprEvent::Workspace { .. }
+                    | HyprEvent::CreateWorkspace { .. }
+                    | HyprEvent::DestroyWorkspace { .. }
+                    | HyprEvent::MoveWorkspace { .. }
+                    | HyprEvent::RenameWorkspace { .. }
+                    | HyprEvent::ActiveSpecial { .. }
+                    | HyprEvent::FocusedMonitor { .. }
+                    | HyprEvent::OpenWindow { .. }
+                    | HyprEvent::CloseWindow { .. }
+                    | HyprEvent::MoveWindow { .. } => WmEvent::Workspaces,
+                    HyprEvent::ActiveWindow { class, title } => {
+                        WmEvent::ActiveWindow(ActiveWindowInfo { class, title })
+                    }
+                    HyprEvent::Urgent { address } => match workspace_of_window(&address) {
+                        Some(id) => WmEvent::Urgent(id),
+                        None => continue,
+                    },
+                    HyprEvent::Connected => WmEvent::Connected,
+                    HyprEvent::Disconnected => WmEvent::Disconnected,
+                    _ => continue,
+                };
+                if sender.send_blocking(event).is_err() {
+                    return;
+                }
+            }
+        });
+
+        receiver
+    }
+
+    fn is_connected(&self) -> bool {
+        hyprland_events::is_connected()
+    }
+
+    fn dispatch(&self, monitor: Option<&str>, action: &Action) -> io::Result<()> {
+        let args = match action {
+            Action::Workspace(id) => format!("workspace {}", id),
+            Action::NamedWorkspace(name) => format!("workspace name:{}", name),
+            Action::ToggleSpecial(Some(name)) => format!("togglespecialworkspace {}", name),
+            Action::ToggleSpecial(None) => "togglespecialworkspace".to_string(),
+            Action::RelativeWorkspace(delta) => format!("workspace m{:+}", delta),
+        };
+
+        // Relative moves act on the focused monitor, so focus the panel's own
+        // monitor first.
+        let mut commands = Vec::new();
+        if let Some(monitor) = monitor {
+            commands.push(format!("dispatch focusmonitor {}", monitor));
+        }
+        commands.push(format!("dispatch {}", args));
+        let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+        hyprland_ipc::batch(&commands).map(|_| ())
+    }
+}
@@ -0,0 +1,473 @@
+use crate::hyprland_ipc;
+use crate::window_manager::{EventHub, HubEvent};
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HyprEvent {
+    Workspace {
+        id: i32,
+        name: String,
+    },
+    CreateWorkspace {
+        id: i32,
+        name: String,
+    },
+    DestroyWorkspace {
+        id: i32,
+        name: String,
+    },
+    MoveWorkspace {
+        id: i32,
+        name: String,
+        monitor: String,
+    },
+    RenameWorkspace {
+        id: i32,
+        name: String,
+    },
+    ActiveSpecial {
+        name: String,
+        monitor: String,
+    },
+    FocusedMonitor {
+        monitor: String,
+        workspace: String,
+    },
+    MonitorAdded {
+        name: String,
+    },
+    MonitorRemoved {
+        name: String,
+    },
+    ActiveWindow {
+        class: String,
+        title: String,
+    },
+    ActiveWindowAddress {
+        address: String,
+    },
+    OpenWindow {
+        address: String,
+        workspace: String,
+        class: String,
+        title: String,
+    },
+    CloseWindow {
+        address: String,
+    },
+    MoveWindow {
+        address: String,
+        workspace_id: i32,
+        workspace_name: String,
+    },
+    WindowTitle {
+        address: String,
+        title: String,
+    },
+    Urgent {
+        address: String,
+    },
+    Fullscreen {
+        active: bool,
+    },
+    Submap {
+        name: String,
+    },
+    ActiveLayout {
+        keyboard: String,
+        layout: String,
+    },
+    Screencast {
+        active: bool,
+        owner: u8,
+    },
+    ConfigReloaded,
+    /// Sent by the panel itself when the event socket comes back.
+    Connected,
+    /// Sent by the panel itself when the event socket can't be reached.
+    Disconnected,
+    Other {
+        name: String,
+        data: String,
+    },
+}
+
+// socket2 addresses lack the `0x` prefix that the JSON replies use.
+fn address(raw: &str) -> String {
+    format!("0x{}", raw.trim_start_matches("0x"))
+}
+
+fn id(raw: &str) -> i32 {
+    raw.parse().unwrap_or_default()
+}
+
+pub fn parse_event(line: &str) -> Option<HyprEvent> {
+    let (name, data) = line.split_once(">>")?;
+    let fields = |n: usize| -> Vec<&str> {
+        let mut parts: Vec<&str> = data.splitn(n, ',').collect();
+        parts.resize(n, "");
+        parts
+    };
+
+    let event = match name {
+        "workspacev2" => {
+            let f = fields(2);
+            HyprEvent::Workspace {
+                id: id(f[0]),
+                name: f[1].to_string(),
+            }
+        }
+        "createworkspacev2" => {
+            let f = fields(2);
+            HyprEvent::CreateWorkspace {
+                id: id(f[0]),
+                name: f[1].to_string(),
+            }
+        }
+        "destroyworkspacev2" => {
+            let f = fields(2);
+            HyprEvent::DestroyWorkspace {
+                id: id(f[0]),
+                name: f[1].to_string(),
+            }
+        }
+        "moveworkspacev2" => {
+            let f = fields(3);
+            HyprEvent::MoveWorkspace {
+                id: id(f[0]),
+                name: f[1].to_string(),
+                monitor: f[2].to_string(),
+            }
+        }
+        "renameworkspace" => {
+            let f = fields(2);
+            HyprEvent::RenameWorkspace {
+                id: id(f[0]),
+                name: f[1].to_string(),
+            }
+        }
+        "activespecial" => {
+            let f = fields(2);
+            HyprEvent::ActiveSpecial {
+                name: f[0].to_string(),
+                monitor: f[1].to_string(),
+            }
+        }
+        "focusedmon" => {
+            let f = fields(2);
+            HyprEvent::FocusedMonitor {
+                monitor: f[0].to_string(),
+                workspace: f[1].to_string(),
+            }
+        }
+        "monitoradded" => HyprEvent::MonitorAdded {
+            name: data.to_string(),
+        },
+        "monitorremoved" => HyprEvent::MonitorRemoved {
+            name: data.to_string(),
+        },
+        "activewindow" => {
+            let f = fields(2);
+            HyprEvent::ActiveWindow {
+                class: f[0].to_string(),
+                title: f[1].to_string(),
+            }
+        }
+        "activewindowv2" => HyprEvent::ActiveWindowAddress {
+            address: address(data),
+        },
+        "openwindow" => {
+            let f = fields(4);
+            HyprEvent::OpenWindow {
+                address: address(f[0]),
+                workspace: f[1].to_string(),
+                class: f[2].to_string(),
+                title: f[3].to_string(),
+            }
+        }
+        "closewindow" => HyprEvent::CloseWindow {
+            address: address(data),
+        },
+        "movewindowv2" => {
+            let f = fields(3);
+            HyprEvent::MoveWindow {
+                address: address(f[0]),
+                workspace_id: id(f[1]),
+                workspace_name: f[2].to_string(),
+            }
+        }
+        "windowtitlev2" => {
+            let f = fields(2);
+            HyprEvent::WindowTitle {
+                address: address(f[0]),
+                title: f[1].to_string(),
+            }
+        }
+        "urgent" => HyprEvent::Urgent {
+            address: address(data),
+        },
+        "fullscreen" => HyprEvent::Fullscreen {
+            active: data == "1",
+        },
+        "submap" => HyprEvent::Submap {
+            name: data.to_string(),
+        },
+        "activelayout" => {
+            let f = fields(2);
+            HyprEvent::ActiveLayout {
+                keyboard: f[0].to_string(),
+                layout: f[1].to_string(),
+            }
+        }
+        "screencast" => {
+            let f = fields(2);
+            HyprEvent::Screencast {
+                active: f[0] == "1",
+                owner: f[1].parse().unwrap_or_default(),
+            }
+        }
+        "configreloaded" => HyprEvent::ConfigReloaded,
+        _ => HyprEvent::Other {
+            name: name.to_string(),
+            data: data.to_string(),
+        },
+    };
+    Some(event)
+}
+
+impl HubEvent for HyprEvent {
+    fn connection(connected: bool) -> Option<Self> {
+        Some(if connected {
+            HyprEvent::Connected
+        } else {
+            HyprEvent::Disconnected
+        })
+    }
+}
+
+static EVENTS: EventHub<HyprEvent> = EventHub::new();
+
+fn connect() -> io::Result<UnixStream> {
+    hyprland_ipc::connect(".socket2.sock")
+}
+
+fn run_event_stream() {
+    EVENTS.run_with_backoff("hyprland", connect, |stream| {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if let Some(event) = parse_event(&line) {
+                EVENTS.broadcast(event);
+            }
+        }
+    })
+}
+
+pub fn is_connected() -> bool {
+    EVENTS.is_connected()
+}
+
+/// Every subscriber gets its own copy of each event from the one shared
+/// socket2 connection, which is opened on the first call.
+pub fn subscribe() -> async_channel::Receiver<HyprEvent> {
+    EVENTS.subscribe(run_event_stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_workspace_events() {
+        assert_eq!(
+            parse_event("workspacev2>>3,3"),
+            Some(HyprEvent::Workspace {
+                id: 3,
+                name: "3".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("createworkspacev2>>-98,special:scratch"),
+            Some(HyprEvent::CreateWorkspace {
+                id: -98,
+                name: "special:scratch".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("destroyworkspacev2>>4,mail"),
+            Some(HyprEvent::DestroyWorkspace {
+                id: 4,
+                name: "mail".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("moveworkspacev2>>2,2,HDMI-A-1"),
+            Some(HyprEvent::MoveWorkspace {
+                id: 2,
+                name: "2".to_string(),
+                monitor: "HDMI-A-1".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("renameworkspace>>5,web, mail"),
+            Some(HyprEvent::RenameWorkspace {
+                id: 5,
+                name: "web, mail".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_monitor_events() {
+        assert_eq!(
+            parse_event("focusedmon>>DP-1,2"),
+            Some(HyprEvent::FocusedMonitor {
+                monitor: "DP-1".to_string(),
+                workspace: "2".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("activespecial>>special:term,DP-1"),
+            Some(HyprEvent::ActiveSpecial {
+                name: "special:term".to_string(),
+                monitor: "DP-1".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("monitoradded>>HDMI-A-1"),
+            Some(HyprEvent::MonitorAdded {
+                name: "HDMI-A-1".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("monitorremoved>>HDMI-A-1"),
+            Some(HyprEvent::MonitorRemoved {
+                name: "HDMI-A-1".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_commas_in_window_titles() {
+        assert_eq!(
+            parse_event("activewindow>>kitty,vim a.txt, b.txt"),
+            Some(HyprEvent::ActiveWindow {
+                class: "kitty".to_string(),
+                title: "vim a.txt, b.txt".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("openwindow>>55d0e1a0,2,firefox,Inbox (3), Mail"),
+            Some(HyprEvent::OpenWindow {
+                address: "0x55d0e1a0".to_string(),
+                workspace: "2".to_string(),
+                class: "firefox".to_string(),
+                title: "Inbox (3), Mail".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("windowtitlev2>>55d0e1a0,a, b"),
+            Some(HyprEvent::WindowTitle {
+                address: "0x55d0e1a0".to_string(),
+                title: "a, b".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn normalises_window_addresses() {
+        assert_eq!(
+            parse_event("activewindowv2>>55d0e1a0"),
+            Some(HyprEvent::ActiveWindowAddress {
+                address: "0x55d0e1a0".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("closewindow>>0x55d0e1a0"),
+            Some(HyprEvent::CloseWindow {
+                address: "0x55d0e1a0".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("urgent>>55d0e1a0"),
+            Some(HyprEvent::Urgent {
+                address: "0x55d0e1a0".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("movewindowv2>>55d0e1a0,4,4"),
+            Some(HyprEvent::MoveWindow {
+                address: "0x55d0e1a0".to_string(),
+                workspace_id: 4,
+                workspace_name: "4".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn parses_state_events() {
+        assert_eq!(
+            parse_event("fullscreen>>1"),
+            Some(HyprEvent::Fullscreen { active: true })
+        );
+        assert_eq!(
+            parse_event("fullscreen>>0"),
+            Some(HyprEvent::Fullscreen { active: false })
+        );
+        assert_eq!(
+            parse_event("submap>>resize"),
+            Some(HyprEvent::Submap {
+                name: "resize".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("activelayout>>at-translated-set-2-keyboard,English (US)"),
+            Some(HyprEvent::ActiveLayout {
+                keyboard: "at-translated-set-2-keyboard".to_string(),
+                layout: "English (US)".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("configreloaded>>"),
+            Some(HyprEvent::ConfigReloaded)
+        );
+    }
+
+    #[test]
+    fn parses_screencast_owner() {
+        assert_eq!(
+            parse_event("screencast>>1,0"),
+            Some(HyprEvent::Screencast {
+                active: true,
+                owner: 0,
+            })
+        );
+        assert_eq!(
+            parse_event("screencast>>0,1"),
+            Some(HyprEvent::Screencast {
+                active: false,
+                owner: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn tolerates_unknown_and_malformed_lines() {
+        assert_eq!(
+            parse_event("pin>>55d0e1a0,1"),
+            Some(HyprEvent::Other {
+                name: "pin".to_string(),
+                data: "55d0e1a0,1".to_string(),
+            })
+        );
+        assert_eq!(parse_event("not an event"), None);
+        // Missing fields come out empty rather than failing the whole event.
+        assert_eq!(
+            parse_event("moveworkspacev2>>2"),
+            Some(HyprEvent::MoveWorkspace {
+                id: 2,
+                name: String::new(),
+                monitor: String::new(),
+            })
+        );
+    }
+}
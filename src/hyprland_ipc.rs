@@ -0,0 +1,325 @@
+use serde_json::Value;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceRef {
+    pub id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub id: i32,
+    pub name: String,
+    pub monitor: String,
+    pub windows: u32,
+    pub has_fullscreen: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Monitor {
+    pub id: i32,
+    pub name: String,
+    pub focused: bool,
+    pub active_workspace: WorkspaceRef,
+    pub special_workspace: WorkspaceRef,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Client {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    pub workspace: WorkspaceRef,
+    pub monitor: i32,
+    pub mapped: bool,
+    pub hidden: bool,
+    pub focus_history_id: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keyboard {
+    pub name: String,
+    pub active_keymap: String,
+    pub main: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bind {
+    pub modmask: u32,
+    pub submap: String,
+    pub key: String,
+    pub description: String,
+    pub dispatcher: String,
+    pub arg: String,
+}
+
+fn str_field(json: &Value, key: &str) -> String {
+    json[key].as_str().unwrap_or_default().to_string()
+}
+
+fn int_field(json: &Value, key: &str) -> i32 {
+    json[key].as_i64().unwrap_or_default() as i32
+}
+
+fn bool_field(json: &Value, key: &str) -> bool {
+    match &json[key] {
+        Value::Bool(b) => *b,
+        // Older Hyprland versions report some flags as numbers.
+        Value::Number(n) => n.as_i64().is_some_and(|n| n != 0),
+        _ => false,
+    }
+}
+
+impl WorkspaceRef {
+    fn from_json(json: &Value) -> Self {
+        WorkspaceRef {
+            id: int_field(json, "id"),
+        }
+    }
+}
+
+impl Workspace {
+    pub fn is_special(&self) -> bool {
+        self.name.starts_with("special")
+    }
+
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Workspace {
+            id: json["id"].as_i64()? as i32,
+            name: str_field(json, "name"),
+            monitor: str_field(json, "monitor"),
+            windows: json["windows"].as_u64().unwrap_or_default() as u32,
+            has_fullscreen: bool_field(json, "hasfullscreen"),
+        })
+    }
+}
+
+impl Monitor {
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Monitor {
+            id: json["id"].as_i64()? as i32,
+            name: str_field(json, "name"),
+            focused: bool_field(json, "focused"),
+            active_workspace: WorkspaceRef::from_json(&json["activeWorkspace"]),
+            special_workspace: WorkspaceRef::from_json(&json["specialWorkspace"]),
+        })
+    }
+}
+
+impl Client {
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Client {
+            address: json["address"].as_str()?.to_string(),
+            class: str_field(json, "class"),
+            title: str_field(json, "title"),
+            workspace: WorkspaceRef::from_json(&json["workspace"]),
+            monitor: int_field(json, "monitor"),
+            mapped: bool_field(json, "mapped"),
+            hidden: bool_field(json, "hidden"),
+            focus_history_id: int_field(json, "focusHistoryID"),
+        })
+    }
+}
+
+impl Keyboard {
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Keyboard {
+            name: json["name"].as_str()?.to_string(),
+            active_keymap: str_field(json, "active_keymap"),
+            main: bool_field(json, "main"),
+        })
+    }
+}
+
+fn runtime_dir() -> io::Result<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR not set"))
+}
+
+// Prefers the instance from our environment, but falls back to the newest
+// running one so the panel survives Hyprland restarting with a new
+// signature, or being started from outside Hyprland.
+fn instance_dir() -> io::Result<PathBuf> {
+    let hypr_dir = runtime_dir()?.join("hypr");
+    // A crashed instance leaves its socket file behind, so only a socket
+    // that accepts connections counts.
+    let is_running = |dir: &Path| UnixStream::connect(dir.join(".socket.sock")).is_ok();
+
+    if let Some(instance) = std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE") {
+        let dir = hypr_dir.join(instance);
+        if is_running(&dir) {
+            return Ok(dir);
+        }
+    }
+
+    fs::read_dir(&hypr_dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|dir| is_running(dir))
+        .max_by_key(|dir| {
+            fs::metadata(dir.join(".socket.sock"))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no running Hyprland instance in {}", hypr_dir.display()),
+            )
+        })
+}
+
+impl Bind {
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Bind {
+            modmask: json["modmask"].as_u64().unwrap_or_default() as u32,
+            submap: str_field(json, "submap"),
+            key: str_field(json, "key"),
+            description: str_field(json, "description"),
+            dispatcher: json["dispatcher"].as_str()?.to_string(),
+            arg: str_field(json, "arg"),
+        })
+    }
+}
+
+// Found once and reused, so requests don't probe for a running instance
+// every time.
+static INSTANCE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Connects to one of the instance's sockets. The instance is looked up again
+/// when the last one found stops accepting connections.
+pub fn connect(name: &str) -> io::Result<UnixStream> {
+    let cached = INSTANCE.lock().unwrap().clone();
+    if let Some(dir) = cached
+        && let Ok(stream) = UnixStream::connect(dir.join(name))
+    {
+        return Ok(stream);
+    }
+
+    let dir = instance_dir()?;
+    let stream = UnixStream::connect(dir.join(name))?;
+    *INSTANCE.lock().unwrap() = Some(dir);
+    Ok(stream)
+}
+
+pub fn request(command: &str) -> io::Result<String> {
+    let mut stream = connect(".socket.sock")?;
+    stream.write_all(command.as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+pub fn request_json(command: &str) -> io::Result<Value> {
+    let response = request(&format!("j/{}", command))?;
+    serde_json::from_str(&response).map_err(io::Error::other)
+}
+
+/// Runs several commands over one connection and returns Hyprland's
+/// combined reply.
+pub fn batch(commands: &[&str]) -> io::Result<String> {
+    request(&format!("[[BATCH]]{}", commands.join(";")))
+}
+
+/// Like `batch`, with the `j/` prefix applied to every command. The replies
+/// come back as consecutive JSON documents and are split apart here.
+pub fn batch_json(commands: &[&str]) -> io::Result<Vec<Value>> {
+    let commands: Vec<String> = commands.iter().map(|c| format!("j/{}", c)).collect();
+    let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+    let response = batch(&commands)?;
+
+    let replies = serde_json::Deserializer::from_str(&response)
+        .into_iter::<Value>()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::other)?;
+    if replies.len() != commands.len() {
+        return Err(io::Error::other(format!(
+            "expected {} batch replies, got {}",
+            commands.len(),
+            replies.len()
+        )));
+    }
+    Ok(replies)
+}
+
+/// Runs a command that answers "ok" on success, such as `dispatch` or
+/// `switchxkblayout`.
+pub fn command(command: &str) -> io::Result<()> {
+    let response = request(command)?;
+    match response.trim() {
+        "ok" => Ok(()),
+        error => Err(io::Error::other(error.to_string())),
+    }
+}
+
+fn parse_list<T>(json: &Value, parse: fn(&Value) -> Option<T>) -> Vec<T> {
+    json.as_array()
+        .map(|items| items.iter().filter_map(parse).collect())
+        .unwrap_or_default()
+}
+
+pub fn parse_workspaces(json: &Value) -> Vec<Workspace> {
+    parse_list(json, Workspace::from_json)
+}
+
+pub fn parse_monitors(json: &Value) -> Vec<Monitor> {
+    parse_list(json, Monitor::from_json)
+}
+
+pub fn parse_clients(json: &Value) -> Vec<Client> {
+    parse_list(json, Client::from_json)
+}
+
+pub fn parse_keyboards(json: &Value) -> Vec<Keyboard> {
+    parse_list(&json["keyboards"], Keyboard::from_json)
+}
+
+pub fn parse_binds(json: &Value) -> Vec<Bind> {
+    parse_list(json, Bind::from_json)
+}
+
+pub fn clients() -> io::Result<Vec<Client>> {
+    Ok(parse_clients(&request_json("clients")?))
+}
+
+/// Returns `None` when no window is focused.
+pub fn active_window() -> io::Result<Option<Client>> {
+    Ok(Client::from_json(&request_json("activewindow")?))
+}
+
+pub fn binds() -> io::Result<Vec<Bind>> {
+    Ok(parse_binds(&request_json("binds")?))
+}
+
+pub fn keyboards() -> io::Result<Vec<Keyboard>> {
+    Ok(parse_keyboards(&request_json("devices")?))
+}
+
+/// The keyboard Hyprland marks as main, or the first one if none is.
+pub fn main_keyboard() -> io::Result<Option<Keyboard>> {
+    let mut keyboards = keyboards()?;
+    let index = keyboards.iter().position(|k| k.main).unwrap_or(0);
+    Ok((index < keyboards.len()).then(|| keyboards.swap_remove(index)))
+}
+
+/// Hyprland reports a single empty string when the config has no errors.
+pub fn config_errors() -> io::Result<Vec<String>> {
+    Ok(request_json("configerrors")?
+        .as_array()
+        .map(|errors| {
+            errors
+                .iter()
+                .filter_map(|e| e.as_str())
+                .filter(|e| !e.trim().is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default())
+}
@@ -1,46 +1,78 @@
+use crate::hyprland_events::{self, HyprEvent};
+use crate::hyprland_ipc::{self, Client};
 use async_channel;
-use std::io::{BufRead, BufReader};
-use std::os::unix::net::UnixStream;
-use std::process::Command;
 
-pub fn start_active_window_listener() -> async_channel::Receiver<String> {
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskbarInfo {
+    pub clients: Vec<Client>,
+    pub focused: Option<String>,
+    pub monitor_id: Option<i32>,
+    pub active_workspace: Option<i32>,
+}
+
+fn get_taskbar_info(monitor: Option<&str>) -> TaskbarInfo {
+    let mut info = TaskbarInfo {
+        clients: vec![],
+        focused: None,
+        monitor_id: None,
+        active_workspace: None,
+    };
+
+    let replies = match hyprland_ipc::batch_json(&["clients", "activewindow", "monitors"]) {
+        Ok(replies) => replies,
+        Err(e) => {
+            eprintln!("[hyprland] Failed to query clients: {}", e);
+            return info;
+        }
+    };
+    let monitors = hyprland_ipc::parse_monitors(&replies[2]);
+    let shown = match monitor {
+        Some(name) => monitors.iter().find(|m| m.name == name),
+        None => monitors.iter().find(|m| m.focused),
+    };
+
+    info.clients = hyprland_ipc::parse_clients(&replies[0])
+        .into_iter()
+        .filter(|c| c.mapped && !c.hidden)
+        .collect();
+    info.focused = replies[1]["address"].as_str().map(str::to_string);
+    info.monitor_id = shown.map(|m| m.id);
+    info.active_workspace = shown.map(|m| m.active_workspace.id);
+
+    info
+}
+
+pub fn start_taskbar_listener(monitor: Option<String>) -> async_channel::Receiver<TaskbarInfo> {
     let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
 
     std::thread::spawn(move || {
-        let runtime_dir = std::env::var("XDG_RUNTIME_DIR").expect("XDG_RUNTIME_DIR not set");
-        let hypr_instance = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
-            .expect("HYPRLAND_INSTANCE_SIGNATURE not set");
-        let socket_path = format!("{}/hypr/{}/.socket2.sock", runtime_dir, hypr_instance);
-
-        loop {
-            match UnixStream::connect(&socket_path) {
-                Ok(stream) => {
-                    let reader = BufReader::new(stream);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            if line.starts_with("activewindow>>") {
-                                let parts: Vec<&str> = line
-                                    .strip_prefix("activewindow>>")
-                                    .unwrap()
-                                    .split(',')
-                                    .collect();
-                                if parts.len() >= 2 {
-                                    let class = parts[0];
-                                    let class_str = if class.is_empty() {
-                                        "Desktop".to_string()
-                                    } else {
-                                        class.to_string()
-                                    };
-                                    let _ = sender.send_blocking(class_str);
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to connect to Hyprland socket: {}", e);
-                    std::thread::sleep(std::time::Duration::from_secs(1));
+        let mut last = get_taskbar_info(monitor.as_deref());
+        if sender.send_blocking(last.clone()).is_err() {
+            return;
+        }
+
+        while let Ok(event) = events.recv_blocking() {
+            if !matches!(
+                event,
+                HyprEvent::OpenWindow { .. }
+                    | HyprEvent::CloseWindow { .. }
+                    | HyprEvent::MoveWindow { .. }
+                    | HyprEvent::WindowTitle { .. }
+                    | HyprEvent::ActiveWindowAddress { .. }
+                    | HyprEvent::Workspace { .. }
+                    | HyprEvent::FocusedMonitor { .. }
+                    | HyprEvent::Connected
+            ) {
+                continue;
+            }
+
+            let info = get_taskbar_info(monitor.as_deref());
+            if info != last {
+                if sender.send_blocking(info.clone()).is_err() {
+                    return;
                 }
+                last = info;
             }
         }
     });
@@ -48,80 +80,206 @@ pub fn start_active_window_listener() -> async_channel::Receiver<String> {
     receiver
 }
 
-fn get_workspace_info() -> (i32, i32) {
-    let output = Command::new("hyprctl")
-        .args(&["workspaces", "-j"])
-        .output();
-    
-    let active_output = Command::new("hyprctl")
-        .args(&["activeworkspace", "-j"])
-        .output();
-
-    let mut active_workspace = 1;
-    let mut max_workspace = 5;
-
-    if let Ok(output) = active_output {
-        if let Ok(json_str) = String::from_utf8(output.stdout) {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&json_str) {
-                if let Some(id) = json["id"].as_i64() {
-                    active_workspace = id as i32;
-                }
+pub fn start_submap_listener() -> async_channel::Receiver<String> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
+
+    std::thread::spawn(move || {
+        while let Ok(event) = events.recv_blocking() {
+            if let HyprEvent::Submap { name } = event
+                && sender.send_blocking(name).is_err()
+            {
+                return;
             }
         }
+    });
+
+    receiver
+}
+
+fn main_keyboard() -> Option<hyprland_ipc::Keyboard> {
+    match hyprland_ipc::main_keyboard() {
+        Ok(keyboard) => keyboard,
+        Err(e) => {
+            eprintln!("[hyprland] Failed to query keyboards: {}", e);
+            None
+        }
     }
+}
+
+pub fn start_layout_listener() -> async_channel::Receiver<String> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
+
+    std::thread::spawn(move || {
+        let mut main = main_keyboard();
+        if let Some(keyboard) = &main {
+            let _ = sender.send_blocking(keyboard.active_keymap.clone());
+        }
 
-    if let Ok(output) = output {
-        if let Ok(json_str) = String::from_utf8(output.stdout) {
-            if let Ok(workspaces) = serde_json::from_str::<Vec<serde_json::Value>>(&json_str) {
-                max_workspace = workspaces
-                    .iter()
-                    .filter_map(|ws| ws["id"].as_i64())
-                    .map(|id| id as i32)
-                    .max()
-                    .unwrap_or(5)
-                    .max(5);
+        while let Ok(event) = events.recv_blocking() {
+            let layout = match event {
+                // Every keyboard reports its own layout changes; only follow
+                // the main one.
+                HyprEvent::ActiveLayout { keyboard, layout }
+                    if main.as_ref().is_none_or(|main| main.name == keyboard) =>
+                {
+                    layout
+                }
+                HyprEvent::Connected => {
+                    main = main_keyboard();
+                    match &main {
+                        Some(keyboard) => keyboard.active_keymap.clone(),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            if sender.send_blocking(layout).is_err() {
+                return;
             }
         }
+    });
+
+    receiver
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FullscreenInfo {
+    /// Monitors whose visible workspace holds a fullscreen window.
+    pub monitors: Vec<String>,
+    pub focused: Option<String>,
+}
+
+impl FullscreenInfo {
+    pub fn contains(&self, monitor: &str) -> bool {
+        self.monitors.iter().any(|m| m == monitor)
+    }
+
+    pub fn focused_is_fullscreen(&self) -> bool {
+        self.focused.as_deref().is_some_and(|m| self.contains(m))
+    }
+}
+
+fn get_fullscreen_info() -> FullscreenInfo {
+    let replies = match hyprland_ipc::batch_json(&["monitors", "workspaces"]) {
+        Ok(replies) => replies,
+        Err(e) => {
+            eprintln!("[hyprland] Failed to query fullscreen state: {}", e);
+            return FullscreenInfo::default();
+        }
+    };
+    let monitors = hyprland_ipc::parse_monitors(&replies[0]);
+    let workspaces = hyprland_ipc::parse_workspaces(&replies[1]);
+    let has_fullscreen =
+        |id: i32| id != 0 && workspaces.iter().any(|ws| ws.id == id && ws.has_fullscreen);
+
+    FullscreenInfo {
+        monitors: monitors
+            .iter()
+            .filter(|m| {
+                has_fullscreen(m.active_workspace.id) || has_fullscreen(m.special_workspace.id)
+            })
+            .map(|m| m.name.clone())
+            .collect(),
+        focused: monitors.iter().find(|m| m.focused).map(|m| m.name.clone()),
     }
+}
+
+// `fullscreen>>` doesn't say which monitor it's for, so every relevant event
+// re-reads the state of all monitors.
+pub fn start_fullscreen_listener() -> async_channel::Receiver<FullscreenInfo> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
+
+    std::thread::spawn(move || {
+        let mut last = get_fullscreen_info();
+        if sender.send_blocking(last.clone()).is_err() {
+            return;
+        }
+
+        while let Ok(event) = events.recv_blocking() {
+            if !matches!(
+                event,
+                HyprEvent::Fullscreen { .. }
+                    | HyprEvent::Workspace { .. }
+                    | HyprEvent::ActiveSpecial { .. }
+                    | HyprEvent::FocusedMonitor { .. }
+                    | HyprEvent::MoveWorkspace { .. }
+                    | HyprEvent::CloseWindow { .. }
+                    | HyprEvent::MoveWindow { .. }
+                    | HyprEvent::MonitorAdded { .. }
+                    | HyprEvent::MonitorRemoved { .. }
+                    | HyprEvent::Connected
+            ) {
+                continue;
+            }
+
+            let info = get_fullscreen_info();
+            if info != last {
+                if sender.send_blocking(info.clone()).is_err() {
+                    return;
+                }
+                last = info;
+            }
+        }
+    });
 
-    (active_workspace, max_workspace)
+    receiver
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreencastSource {
+    Monitor,
+    Window,
 }
 
-pub fn start_workspace_listener() -> async_channel::Receiver<(i32, i32)> {
+/// Sends the shared source while a screencast is running, `None` once it ends.
+pub fn start_screencast_listener() -> async_channel::Receiver<Option<ScreencastSource>> {
     let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
+
+    std::thread::spawn(move || {
+        while let Ok(event) = events.recv_blocking() {
+            let source = match event {
+                HyprEvent::Screencast { active: false, .. } | HyprEvent::Disconnected => None,
+                HyprEvent::Screencast { owner: 1, .. } => Some(ScreencastSource::Window),
+                HyprEvent::Screencast { .. } => Some(ScreencastSource::Monitor),
+                _ => continue,
+            };
+            if sender.send_blocking(source).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
 
-    let initial_state = get_workspace_info();
-    let _ = sender.send_blocking(initial_state);
+fn config_errors() -> Vec<String> {
+    hyprland_ipc::config_errors().unwrap_or_else(|e| {
+        eprintln!("[hyprland] Failed to query config errors: {}", e);
+        vec![]
+    })
+}
+
+pub fn start_config_error_listener() -> async_channel::Receiver<Vec<String>> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = hyprland_events::subscribe();
 
     std::thread::spawn(move || {
-        let runtime_dir = std::env::var("XDG_RUNTIME_DIR").expect("XDG_RUNTIME_DIR not set");
-        let hypr_instance = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
-            .expect("HYPRLAND_INSTANCE_SIGNATURE not set");
-        let socket_path = format!("{}/hypr/{}/.socket2.sock", runtime_dir, hypr_instance);
-
-        loop {
-            match UnixStream::connect(&socket_path) {
-                Ok(stream) => {
-                    let reader = BufReader::new(stream);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            if line.starts_with("workspace>>")
-                                || line.starts_with("destroyworkspace>>")
-                                || line.starts_with("createworkspace>>")
-                            {
-                                let workspace_info = get_workspace_info();
-                                let _ = sender.send_blocking(workspace_info);
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Failed to connect to Hyprland socket: {}", e);
-                    std::thread::sleep(std::time::Duration::from_secs(1));
-                }
+        if sender.send_blocking(config_errors()).is_err() {
+            return;
+        }
+
+        while let Ok(event) = events.recv_blocking() {
+            if matches!(event, HyprEvent::ConfigReloaded | HyprEvent::Connected)
+                && sender.send_blocking(config_errors()).is_err()
+            {
+                return;
             }
         }
     });
 
     receiver
-}
\ No newline at end of file
+}
@@ -0,0 +1,595 @@
+use crate::config::ModuleConfig;
+use crate::desktop_entries;
+use crate::taskbar_widget::{TaskbarFilter, TaskbarOptions};
+use crate::window_title::TitleFormat;
+use crate::{
+    battery_listener, battery_widget, bluetooth_listener, hyprland_ipc, hyprland_listener, paths,
+    taskbar_widget, volume_listener, wifi_listener, window_manager, workspace_widget,
+};
+use chrono::format::StrftimeItems;
+use chrono::{Local, Timelike};
+use gtk4::{Label, Orientation, Widget};
+use gtk4::{gdk, glib, prelude::*};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PanelContext {
+    pub monitor: Option<String>,
+    pub orientation: Orientation,
+}
+
+pub fn build_section(modules: &[ModuleConfig], spacing: i32, ctx: &PanelContext) -> gtk4::Box {
+    let section = gtk4::Box::new(ctx.orientation, spacing);
+    for module in modules {
+        if let Some(widget) = build_module(module, ctx) {
+            section.append(&widget);
+        }
+    }
+    section
+}
+
+// Modules that read from Hyprland's IPC directly rather than through the
+// window manager backend.
+const HYPRLAND_ONLY: [&str; 5] = [
+    "taskbar",
+    "submap",
+    "keyboard_layout",
+    "screencast",
+    "config_errors",
+];
+
+fn build_module(module: &ModuleConfig, ctx: &PanelContext) -> Option<Widget> {
+    if HYPRLAND_ONLY.contains(&module.name.as_str()) && !window_manager::is_hyprland() {
+        eprintln!(
+            "[modules] {} needs Hyprland, skipping it under {}",
+            module.name,
+            window_manager::current().name()
+        );
+        return None;
+    }
+
+    let widget = match module.name.as_str() {
+        "logo" => build_logo(module).upcast(),
+        "active_window" => build_active_window(module, ctx).upcast(),
+        "workspaces" => build_workspaces(module, ctx).upcast(),
+        "taskbar" => build_taskbar(module, ctx).upcast(),
+        "submap" => build_submap(module).upcast(),
+        "keyboard_layout" => build_keyboard_layout(module).upcast(),
+        "screencast" => build_screencast(module).upcast(),
+        "config_errors" => build_config_errors().upcast(),
+        "battery" => build_battery(module, ctx).upcast(),
+        "volume" => build_volume(module).upcast(),
+        "wifi" => build_wifi(module).upcast(),
+        "bluetooth" => build_bluetooth(module).upcast(),
+        "clock" => build_clock(module).upcast(),
+        other => {
+            eprintln!("[modules] Unknown module: {}", other);
+            return None;
+        }
+    };
+    Some(widget)
+}
+
+fn set_logo(logo: &gtk4::Image, module: &ModuleConfig) {
+    let path = match module.str("path") {
+        Some(path) => {
+            let path = paths::resolve(path);
+            if path.is_file() {
+                Some(path)
+            } else {
+                eprintln!(
+                    "[logo] {} not found, using the default logo",
+                    path.display()
+                );
+                None
+            }
+        }
+        None => paths::find_file("logo.svg"),
+    };
+
+    match path {
+        Some(path) => logo.set_from_file(Some(path)),
+        None => match gdk::Texture::from_bytes(&glib::Bytes::from_static(paths::DEFAULT_LOGO)) {
+            Ok(texture) => logo.set_paintable(Some(&texture)),
+            Err(e) => eprintln!("[logo] Failed to load the built-in logo: {}", e),
+        },
+    }
+}
+
+thread_local! {
+    // Run when logo.svg is replaced; each returns false once its logo is gone.
+    static LOGO_RELOADS: RefCell<Vec<Box<dyn Fn() -> bool>>> = RefCell::default();
+}
+
+/// Reloads every logo from its file, leaving the rest of the panel alone.
+pub fn reload_logos() {
+    LOGO_RELOADS.with_borrow_mut(|reloads| reloads.retain(|reload| reload()));
+}
+
+// Shows the icon of `class`'s app when there is one, the logo otherwise.
+fn show_logo(logo: &gtk4::Image, module: &ModuleConfig, class: Option<&str>) {
+    if !class.is_some_and(|class| desktop_entries::set_app_icon(logo, class)) {
+        set_logo(logo, module);
+    }
+}
+
+fn build_logo(module: &ModuleConfig) -> gtk4::Image {
+    let logo = gtk4::Image::new();
+    set_logo(&logo, module);
+    logo.set_pixel_size(module.int("size").unwrap_or(16) as i32);
+
+    if module
+        .bool("cheatsheet")
+        .unwrap_or_else(window_manager::is_hyprland)
+    {
+        let click = gtk4::GestureClick::new();
+        click.set_button(gdk::BUTTON_PRIMARY);
+        click.connect_released(|gesture, _n_press, _x, _y| {
+            gesture.set_state(gtk4::EventSequenceState::Claimed);
+            if let Some(widget) = gesture.widget()
+                && let Err(e) = widget.activate_action("app.cheatsheet", None)
+            {
+                eprintln!("[logo] Failed to open the cheatsheet: {}", e);
+            }
+        });
+        logo.add_controller(click);
+    }
+
+    // The class of the window being followed, so a reload keeps its icon.
+    let class: Rc<RefCell<Option<String>>> = Rc::default();
+    {
+        let module = module.clone();
+        let class = class.clone();
+        let logo_weak = logo.downgrade();
+        LOGO_RELOADS.with_borrow_mut(|reloads| {
+            reloads.push(Box::new(move || {
+                let Some(logo) = logo_weak.upgrade() else {
+                    return false;
+                };
+                show_logo(&logo, &module, class.borrow().as_deref());
+                true
+            }))
+        });
+    }
+
+    if module.bool("follow_active").unwrap_or(false) {
+        let module = module.clone();
+        let receiver = window_manager::start_active_window_listener();
+        let logo_weak = logo.downgrade();
+        glib::spawn_future_local(async move {
+            while let Ok(window) = receiver.recv().await {
+                let Some(logo) = logo_weak.upgrade() else {
+                    break;
+                };
+                show_logo(&logo, &module, Some(&window.class));
+                *class.borrow_mut() = Some(window.class);
+            }
+        });
+    }
+
+    logo
+}
+
+// Dims window manager modules while the compositor can't be reached.
+fn track_connection(widget: &impl IsA<Widget>) {
+    let receiver = window_manager::start_connection_listener();
+    let widget_weak = widget.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(connected) = receiver.recv().await {
+            let Some(widget) = widget_weak.upgrade() else {
+                break;
+            };
+            if connected {
+                widget.remove_css_class("disconnected");
+                widget.set_tooltip_text(None);
+            } else {
+                widget.add_css_class("disconnected");
+                let tooltip = format!("Disconnected from {}", window_manager::current().name());
+                widget.set_tooltip_text(Some(&tooltip));
+            }
+        }
+    });
+}
+
+fn build_active_window(module: &ModuleConfig, ctx: &PanelContext) -> gtk4::Box {
+    let container = gtk4::Box::new(ctx.orientation, 6);
+
+    let icon = module.bool("icon").unwrap_or(false).then(|| {
+        let icon = gtk4::Image::new();
+        icon.set_pixel_size(module.int("icon_size").unwrap_or(16) as i32);
+        icon.set_visible(false);
+        container.append(&icon);
+        icon
+    });
+
+    let format = TitleFormat::from_config(module);
+    let label = Label::builder().label(&format.placeholder).build();
+    if let Some(max_width) = module.int("max_width") {
+        label.set_max_width_chars(max_width as i32);
+        label.set_ellipsize(pango::EllipsizeMode::End);
+    }
+    container.append(&label);
+    track_connection(&container);
+
+    let receiver = window_manager::start_active_window_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(window) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            label.set_label(&format.format(&window));
+            if let Some(icon) = &icon {
+                icon.set_visible(desktop_entries::set_app_icon(icon, &window.class));
+            }
+        }
+    });
+
+    container
+}
+
+fn build_workspaces(module: &ModuleConfig, ctx: &PanelContext) -> gtk4::Box {
+    let min_count = module.int("min").unwrap_or(5) as i32;
+    let (widget, updater) =
+        workspace_widget::build_workspace_widget(ctx.orientation, min_count, ctx.monitor.clone());
+    track_connection(&widget);
+
+    let receiver = window_manager::start_workspace_listener(ctx.monitor.clone());
+    let widget_weak = widget.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            if widget_weak.upgrade().is_none() {
+                break;
+            }
+            updater(info);
+        }
+    });
+
+    widget
+}
+
+fn build_taskbar(module: &ModuleConfig, ctx: &PanelContext) -> gtk4::Box {
+    let filter = match module.str("filter") {
+        None | Some("all") => TaskbarFilter::All,
+        Some("monitor") => TaskbarFilter::Monitor,
+        Some("workspace") => TaskbarFilter::Workspace,
+        Some(other) => {
+            eprintln!("[taskbar] Unknown filter {:?}, showing all windows", other);
+            TaskbarFilter::All
+        }
+    };
+    let options = TaskbarOptions {
+        filter,
+        group: module.bool("group").unwrap_or(false),
+        max_width: module.int("max_width").unwrap_or(20) as i32,
+        icon_size: module.int("icon_size").unwrap_or(16) as i32,
+    };
+    let (widget, updater) = taskbar_widget::build_taskbar_widget(ctx.orientation, options);
+    track_connection(&widget);
+
+    let receiver = hyprland_listener::start_taskbar_listener(ctx.monitor.clone());
+    let widget_weak = widget.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            if widget_weak.upgrade().is_none() {
+                break;
+            }
+            updater(info);
+        }
+    });
+
+    widget
+}
+
+fn build_submap(module: &ModuleConfig) -> Label {
+    let format = module.str("format").unwrap_or("{}").to_string();
+    let label = Label::new(None);
+    label.add_css_class("submap");
+    label.set_visible(false);
+
+    let receiver = hyprland_listener::start_submap_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(name) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            // Hyprland reports an empty name when returning to the default map.
+            if name.is_empty() {
+                label.set_visible(false);
+            } else {
+                label.set_label(&format.replace("{}", &name));
+                label.set_visible(true);
+            }
+        }
+    });
+
+    label
+}
+
+// Layouts without a configured code fall back to their first two letters,
+// e.g. "English (US)" becomes "EN".
+fn layout_code(codes: &HashMap<String, String>, layout: &str) -> String {
+    match codes.get(layout) {
+        Some(code) => code.clone(),
+        None => layout.chars().take(2).collect::<String>().to_uppercase(),
+    }
+}
+
+fn build_keyboard_layout(module: &ModuleConfig) -> Label {
+    let codes: HashMap<String, String> = module
+        .table("layouts")
+        .map(|layouts| {
+            layouts
+                .iter()
+                .filter_map(|(name, code)| Some((name.clone(), code.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let label = Label::new(None);
+    label.add_css_class("keyboard-layout");
+
+    let click = gtk4::GestureClick::new();
+    click.set_button(gdk::BUTTON_PRIMARY);
+    click.connect_released(|gesture, _n_press, _x, _y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        std::thread::spawn(|| {
+            let keyboard = match hyprland_ipc::main_keyboard() {
+                Ok(Some(keyboard)) => keyboard,
+                Ok(None) => return,
+                Err(e) => {
+                    eprintln!("[keyboard_layout] Failed to query keyboards: {}", e);
+                    return;
+                }
+            };
+            // A hyprctl command of its own rather than a dispatcher.
+            let command = format!("switchxkblayout {} next", keyboard.name);
+            if let Err(e) = hyprland_ipc::command(&command) {
+                eprintln!("[keyboard_layout] Failed to switch layout: {}", e);
+            }
+        });
+    });
+    label.add_controller(click);
+
+    let receiver = hyprland_listener::start_layout_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(layout) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            label.set_label(&layout_code(&codes, &layout));
+            label.set_tooltip_text(Some(&layout));
+        }
+    });
+
+    label
+}
+
+fn build_screencast(module: &ModuleConfig) -> Label {
+    let monitor_text = module
+        .str("monitor")
+        .unwrap_or("● Sharing screen")
+        .to_string();
+    let window_text = module
+        .str("window")
+        .unwrap_or("● Sharing window")
+        .to_string();
+    let label = Label::new(None);
+    label.add_css_class("screencast");
+    label.set_visible(false);
+
+    let receiver = hyprland_listener::start_screencast_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        use hyprland_listener::ScreencastSource;
+        while let Ok(source) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            label.remove_css_class("monitor");
+            label.remove_css_class("window");
+            match source {
+                Some(ScreencastSource::Monitor) => {
+                    label.set_label(&monitor_text);
+                    label.add_css_class("monitor");
+                }
+                Some(ScreencastSource::Window) => {
+                    label.set_label(&window_text);
+                    label.add_css_class("window");
+                }
+                None => {}
+            }
+            label.set_visible(source.is_some());
+        }
+    });
+
+    label
+}
+
+fn build_config_errors() -> Label {
+    let label = Label::new(None);
+    label.add_css_class("config-errors");
+    label.set_visible(false);
+
+    let receiver = hyprland_listener::start_config_error_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(errors) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            label.set_label(&format!("⚠ {}", errors.len()));
+            label.set_tooltip_text(Some(&errors.join("\n")));
+            label.set_visible(!errors.is_empty());
+        }
+    });
+
+    label
+}
+
+fn build_battery(_module: &ModuleConfig, ctx: &PanelContext) -> gtk4::Box {
+    let (battery_widget, battery_updater) = battery_widget::build_battery_widget(ctx.orientation);
+
+    let receiver = battery_listener::start_battery_listener();
+    let widget_weak = battery_widget.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            if widget_weak.upgrade().is_none() {
+                break;
+            }
+            battery_updater(info);
+        }
+    });
+
+    battery_widget
+}
+
+fn build_volume(module: &ModuleConfig) -> Label {
+    let step = module.int("step").unwrap_or(5);
+    let label = Label::builder().label("...").build();
+    label.add_css_class("volume");
+
+    let scroll = gtk4::EventControllerScroll::new(
+        gtk4::EventControllerScrollFlags::VERTICAL | gtk4::EventControllerScrollFlags::DISCRETE,
+    );
+    scroll.connect_scroll(move |_controller, _dx, dy| {
+        if dy < 0.0 {
+            volume_listener::change_volume(step);
+        } else if dy > 0.0 {
+            volume_listener::change_volume(-step);
+        }
+        glib::Propagation::Stop
+    });
+    label.add_controller(scroll);
+
+    let click = gtk4::GestureClick::new();
+    click.set_button(gdk::BUTTON_PRIMARY);
+    click.connect_released(|gesture, _n_press, _x, _y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        volume_listener::toggle_mute();
+    });
+    label.add_controller(click);
+
+    // The HUD bar is driven by the same audio server events, so changes made
+    // here show up there as well.
+    let receiver = volume_listener::start_volume_info_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(update) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            let info = update.info;
+            let percent = (info.volume * 100.0).round() as i32;
+            let icon = match percent {
+                _ if info.muted => "󰝟",
+                0..=33 => "󰕿",
+                34..=66 => "󰖀",
+                _ => "󰕾",
+            };
+            label.set_label(&format!("{} {}%", icon, percent));
+            if info.muted {
+                label.add_css_class("muted");
+            } else {
+                label.remove_css_class("muted");
+            }
+        }
+    });
+
+    label
+}
+
+fn build_wifi(_module: &ModuleConfig) -> Label {
+    let label = Label::builder().label("...").build();
+
+    let receiver = wifi_listener::start_wifi_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            let text = if info.connected {
+                match info.signal {
+                    Some(s) if s >= 75 => "󰤨",
+                    Some(s) if s >= 50 => "󰤥",
+                    Some(s) if s >= 25 => "󰤢",
+                    Some(_) => "󰤟",
+                    None => "󰤨",
+                }
+            } else {
+                "󰤭"
+            };
+            label.set_label(text);
+        }
+    });
+
+    label
+}
+
+fn build_bluetooth(_module: &ModuleConfig) -> Label {
+    let label = Label::builder().label("...").build();
+
+    let receiver = bluetooth_listener::start_bluetooth_listener();
+    let label_weak = label.downgrade();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            let Some(label) = label_weak.upgrade() else {
+                break;
+            };
+            if !info.enabled {
+                label.set_visible(false);
+            } else {
+                let text = if info.connected_devices.is_empty() {
+                    "󰂯".to_string()
+                } else {
+                    format!("󰂱 {}", info.connected_devices.join(", "))
+                };
+                label.set_label(&text);
+                label.set_visible(true);
+            }
+        }
+    });
+
+    label
+}
+
+fn build_clock(module: &ModuleConfig) -> Label {
+    let mut format = module.str("format").unwrap_or("%a %b %d %H:%M").to_string();
+    if StrftimeItems::new(&format).parse().is_err() {
+        eprintln!("[clock] Invalid format {:?}, using the default", format);
+        format = "%a %b %d %H:%M".to_string();
+    }
+    let label = Label::builder()
+        .label(format!("{}", Local::now().format(&format)))
+        .build();
+
+    let now: chrono::DateTime<_> = Local::now();
+    let seconds_until_next_minute = 60 - now.second();
+
+    let label_weak = label.downgrade();
+    glib::timeout_add_seconds_local(seconds_until_next_minute, move || {
+        let Some(label) = label_weak.upgrade() else {
+            return glib::ControlFlow::Break;
+        };
+        label.set_label(&format!("{}", Local::now().format(&format)));
+
+        let label_weak = label.downgrade();
+        let format = format.clone();
+        glib::timeout_add_seconds_local(60, move || {
+            let Some(label) = label_weak.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            label.set_label(&format!("{}", Local::now().format(&format)));
+            glib::ControlFlow::Continue
+        });
+
+        glib::ControlFlow::Break
+    });
+
+    label
+}
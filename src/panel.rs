@@ -0,0 +1,247 @@
+use crate::config::{Config, FullscreenMode, Position};
+use crate::hyprland_listener::{self, FullscreenInfo};
+use crate::modules::{self, PanelContext};
+use crate::window_manager;
+use gtk4::gdk::{self, Display};
+use gtk4::prelude::*;
+use gtk4::{Application, ApplicationWindow, CenterBox, Orientation, glib};
+use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+pub struct Panel {
+    monitor: gdk::Monitor,
+    window: ApplicationWindow,
+    fullscreen: bool,
+}
+
+impl Panel {
+    fn connector(&self) -> Option<String> {
+        self.monitor.connector().map(|c| c.to_string())
+    }
+}
+
+fn build_window(app: &Application, monitor: &gdk::Monitor) -> ApplicationWindow {
+    let window = ApplicationWindow::builder()
+        .application(app)
+        .decorated(false)
+        .build();
+
+    window.init_layer_shell();
+    window.set_namespace(Some("hypr-panel"));
+    window.set_monitor(Some(monitor));
+    window.set_layer(Layer::Top);
+    window.auto_exclusive_zone_enable();
+
+    window
+}
+
+fn apply_position(window: &ApplicationWindow, config: &Config) {
+    let (edge, sides) = match config.position {
+        Position::Top => (Edge::Top, [Edge::Left, Edge::Right]),
+        Position::Bottom => (Edge::Bottom, [Edge::Left, Edge::Right]),
+        Position::Left => (Edge::Left, [Edge::Top, Edge::Bottom]),
+        Position::Right => (Edge::Right, [Edge::Top, Edge::Bottom]),
+    };
+    for e in [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+        window.set_anchor(e, e == edge || sides.contains(&e));
+    }
+
+    if config.position.is_vertical() {
+        window.set_default_size(config.size, -1);
+    } else {
+        window.set_default_size(-1, config.size);
+    }
+}
+
+fn apply_fullscreen(panel: &Panel, config: &Config) {
+    let mode = if panel.fullscreen {
+        config.fullscreen
+    } else {
+        FullscreenMode::Ignore
+    };
+    let layer = match mode {
+        FullscreenMode::Lower => Layer::Bottom,
+        _ => Layer::Top,
+    };
+    panel.window.set_layer(layer);
+    panel.window.set_visible(mode != FullscreenMode::Hide);
+}
+
+fn context(panel: &Panel, config: &Config) -> PanelContext {
+    let orientation = if config.position.is_vertical() {
+        Orientation::Vertical
+    } else {
+        Orientation::Horizontal
+    };
+    PanelContext {
+        monitor: panel.connector(),
+        orientation,
+    }
+}
+
+fn build_content(panel: &Panel, config: &Config) {
+    let ctx = context(panel, config);
+    apply_position(&panel.window, config);
+
+    let container = CenterBox::new();
+    container.set_orientation(ctx.orientation);
+    if config.position.is_vertical() {
+        container.set_margin_top(config.margin);
+        container.set_margin_bottom(config.margin);
+    } else {
+        container.set_margin_start(config.margin);
+        container.set_margin_end(config.margin);
+    }
+
+    let start_box = modules::build_section(&config.start, config.spacing, &ctx);
+    let center_box = modules::build_section(&config.center, config.spacing, &ctx);
+    let end_box = modules::build_section(&config.end, config.spacing, &ctx);
+
+    container.set_start_widget(Some(&start_box));
+    container.set_center_widget(Some(&center_box));
+    container.set_end_widget(Some(&end_box));
+
+    // Replacing the child drops the previous modules; their update loops
+    // only hold weak references and wind down on their own.
+    panel.window.set_child(Some(&container));
+    apply_fullscreen(panel, config);
+}
+
+fn current_monitors(display: &Display) -> Vec<gdk::Monitor> {
+    let model = display.monitors();
+    (0..model.n_items())
+        .filter_map(|i| model.item(i)?.downcast::<gdk::Monitor>().ok())
+        .collect()
+}
+
+fn sync_panels(
+    app: &Application,
+    display: &Display,
+    panels: &mut Vec<Panel>,
+    config: &Config,
+    fullscreen: &FullscreenInfo,
+) {
+    let monitors = current_monitors(display);
+
+    panels.retain(|panel| {
+        let keep = monitors.contains(&panel.monitor);
+        if !keep {
+            panel.window.destroy();
+        }
+        keep
+    });
+
+    for monitor in monitors {
+        if panels.iter().any(|p| p.monitor == monitor) {
+            continue;
+        }
+        let panel = Panel {
+            window: build_window(app, &monitor),
+            fullscreen: monitor
+                .connector()
+                .is_some_and(|name| fullscreen.contains(&name)),
+            monitor,
+        };
+        build_content(&panel, config);
+        // A panel that starts out hidden is shown once fullscreen ends.
+        if panel.window.is_visible() {
+            panel.window.present();
+        }
+        panels.push(panel);
+    }
+}
+
+/// Applies a reloaded config. Only the sections whose modules changed are
+/// rebuilt; the rest keep their widgets and listeners.
+pub fn rebuild_panels(panels: &[Panel], old: &Config, config: &Config) {
+    let layout_changed = old.position != config.position
+        || old.margin != config.margin
+        || old.spacing != config.spacing;
+
+    for panel in panels {
+        let container = panel.window.child().and_downcast::<CenterBox>();
+        let Some(container) = container.filter(|_| !layout_changed) else {
+            build_content(panel, config);
+            continue;
+        };
+
+        let ctx = context(panel, config);
+        let build = |modules| modules::build_section(modules, config.spacing, &ctx);
+        if old.start != config.start {
+            container.set_start_widget(Some(&build(&config.start)));
+        }
+        if old.center != config.center {
+            container.set_center_widget(Some(&build(&config.center)));
+        }
+        if old.end != config.end {
+            container.set_end_widget(Some(&build(&config.end)));
+        }
+        apply_position(&panel.window, config);
+        apply_fullscreen(panel, config);
+    }
+}
+
+/// Opens a panel on every monitor and keeps them in step with the monitors
+/// and their fullscreen state. `focused_fullscreen` follows whether the
+/// focused monitor shows a fullscreen window.
+pub fn start_panels(
+    app: &Application,
+    config: Rc<RefCell<Config>>,
+    focused_fullscreen: Rc<Cell<bool>>,
+) -> Rc<RefCell<Vec<Panel>>> {
+    let panels = Rc::new(RefCell::new(Vec::new()));
+
+    let Some(display) = Display::default() else {
+        eprintln!("[panel] No display available");
+        return panels;
+    };
+
+    let fullscreen = Rc::new(RefCell::new(FullscreenInfo::default()));
+    sync_panels(
+        app,
+        &display,
+        &mut panels.borrow_mut(),
+        &config.borrow(),
+        &fullscreen.borrow(),
+    );
+
+    let app = app.clone();
+    let panels_clone = panels.clone();
+    let config_clone = config.clone();
+    let fullscreen_clone = fullscreen.clone();
+    let display_clone = display.clone();
+    display
+        .monitors()
+        .connect_items_changed(move |_model, _position, _removed, _added| {
+            sync_panels(
+                &app,
+                &display_clone,
+                &mut panels_clone.borrow_mut(),
+                &config_clone.borrow(),
+                &fullscreen_clone.borrow(),
+            );
+        });
+
+    // Fullscreen tracking is only implemented for Hyprland.
+    if !window_manager::is_hyprland() {
+        return panels;
+    }
+    let receiver = hyprland_listener::start_fullscreen_listener();
+    let panels_clone = panels.clone();
+    glib::spawn_future_local(async move {
+        while let Ok(info) = receiver.recv().await {
+            for panel in panels_clone.borrow_mut().iter_mut() {
+                let fullscreen = panel.connector().is_some_and(|name| info.contains(&name));
+                if fullscreen != panel.fullscreen {
+                    panel.fullscreen = fullscreen;
+                    apply_fullscreen(panel, &config.borrow());
+                }
+            }
+            focused_fullscreen.set(info.focused_is_fullscreen());
+            *fullscreen.borrow_mut() = info;
+        }
+    });
+
+    panels
+}
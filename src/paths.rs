@@ -0,0 +1,68 @@
+use std::env;
+use std::path::PathBuf;
+
+pub const DEFAULT_STYLE: &str = include_str!("../style.css");
+pub const DEFAULT_LOGO: &[u8] = include_bytes!("../logo.svg");
+
+fn env_dir(var: &str) -> Option<PathBuf> {
+    env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+fn home_dir(relative: &str) -> Option<PathBuf> {
+    env_dir("HOME").map(|home| home.join(relative))
+}
+
+pub fn config_dir() -> Option<PathBuf> {
+    env_dir("XDG_CONFIG_HOME")
+        .or_else(|| home_dir(".config"))
+        .map(|dir| dir.join("hypr-panel"))
+}
+
+/// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`, e.g. for finding desktop
+/// entries.
+pub fn xdg_data_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env_dir("XDG_DATA_HOME")
+        .or_else(|| home_dir(".local/share"))
+        .into_iter()
+        .collect();
+
+    let system = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(
+        system
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+    dirs
+}
+
+fn data_dirs() -> Vec<PathBuf> {
+    xdg_data_dirs()
+        .into_iter()
+        .map(|dir| dir.join("hypr-panel"))
+        .collect()
+}
+
+pub fn search_dirs() -> Vec<PathBuf> {
+    config_dir().into_iter().chain(data_dirs()).collect()
+}
+
+pub fn find_file(name: &str) -> Option<PathBuf> {
+    search_dirs()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+pub fn resolve(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    match config_dir() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
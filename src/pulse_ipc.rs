@@ -0,0 +1,366 @@
+use crate::volume_listener::VolumeInfo;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+// PulseAudio's native protocol, which pipewire-pulse speaks as well. Every
+// packet starts with five big-endian u32s (payload length, channel, two
+// offset words, flags), and commands carry a tagstruct payload: each value
+// is prefixed with a byte naming its type.
+const COMMAND_CHANNEL: u32 = u32::MAX;
+const PROTOCOL_VERSION: u32 = 32;
+// Client names are sent as a property list from this version on.
+const MIN_SERVER_VERSION: u32 = 13;
+const COOKIE_LENGTH: usize = 256;
+
+const COMMAND_ERROR: u32 = 0;
+const COMMAND_REPLY: u32 = 2;
+const COMMAND_AUTH: u32 = 8;
+const COMMAND_SET_CLIENT_NAME: u32 = 9;
+const COMMAND_GET_SINK_INFO: u32 = 21;
+const COMMAND_SUBSCRIBE: u32 = 35;
+const COMMAND_SUBSCRIBE_EVENT: u32 = 66;
+
+const SUBSCRIPTION_MASK_SINK: u32 = 0x0001;
+const SUBSCRIPTION_MASK_SERVER: u32 = 0x0080;
+const EVENT_FACILITY_MASK: u32 = 0x000f;
+const EVENT_SINK: u32 = 0x0000;
+// Sent when e.g. the default sink changes.
+const EVENT_SERVER: u32 = 0x0007;
+
+const INVALID_INDEX: u32 = u32::MAX;
+const VOLUME_NORM: f32 = 0x10000 as f32;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[derive(Default)]
+struct TagWriter(Vec<u8>);
+
+impl TagWriter {
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.0.push(b'L');
+        self.0.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn string(&mut self, value: &str) -> &mut Self {
+        self.0.push(b't');
+        self.0.extend_from_slice(value.as_bytes());
+        self.0.push(0);
+        self
+    }
+
+    fn arbitrary(&mut self, data: &[u8]) -> &mut Self {
+        self.0.push(b'x');
+        self.0.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.0.extend_from_slice(data);
+        self
+    }
+
+    fn proplist(&mut self, properties: &[(&str, &str)]) -> &mut Self {
+        self.0.push(b'P');
+        for (key, value) in properties {
+            let mut value = value.as_bytes().to_vec();
+            value.push(0);
+            self.string(key).u32(value.len() as u32).arbitrary(&value);
+        }
+        // A null string ends the list.
+        self.0.push(b'N');
+        self
+    }
+}
+
+struct TagReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TagReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        TagReader { data }
+    }
+
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < n {
+            return Err(invalid("truncated pulse packet"));
+        }
+        let (taken, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn tag(&mut self, expected: u8) -> io::Result<()> {
+        match self.take(1)?[0] {
+            tag if tag == expected => Ok(()),
+            _ => Err(invalid("unexpected value in pulse packet")),
+        }
+    }
+
+    fn raw_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        self.tag(b'L')?;
+        self.raw_u32()
+    }
+
+    fn skip_string(&mut self) -> io::Result<()> {
+        match self.take(1)?[0] {
+            b'N' => Ok(()),
+            b't' => {
+                let end = self
+                    .data
+                    .iter()
+                    .position(|b| *b == 0)
+                    .ok_or_else(|| invalid("unterminated string in pulse packet"))?;
+                self.take(end + 1)?;
+                Ok(())
+            }
+            _ => Err(invalid("unexpected value in pulse packet")),
+        }
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.take(1)?[0] {
+            b'1' => Ok(true),
+            b'0' => Ok(false),
+            _ => Err(invalid("unexpected value in pulse packet")),
+        }
+    }
+
+    // Sample format and channel count as u8s, then the rate as a u32.
+    fn skip_sample_spec(&mut self) -> io::Result<()> {
+        self.tag(b'a')?;
+        self.take(6)?;
+        Ok(())
+    }
+
+    fn skip_channel_map(&mut self) -> io::Result<()> {
+        self.tag(b'm')?;
+        let channels = self.take(1)?[0] as usize;
+        self.take(channels)?;
+        Ok(())
+    }
+
+    fn cvolume(&mut self) -> io::Result<Vec<u32>> {
+        self.tag(b'v')?;
+        let channels = self.take(1)?[0];
+        (0..channels).map(|_| self.raw_u32()).collect()
+    }
+}
+
+fn socket_path() -> io::Result<PathBuf> {
+    // `PULSE_SERVER` can also name a TCP server, which isn't supported.
+    if let Ok(server) = std::env::var("PULSE_SERVER")
+        && let Some(path) = server.strip_prefix("unix:")
+    {
+        return Ok(PathBuf::from(path));
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("pulse/native"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR not set"))
+}
+
+// PulseAudio checks this against its own copy; pipewire-pulse ignores it, so
+// zeros do when there's no cookie file.
+fn cookie() -> Vec<u8> {
+    let env_dir = |var: &str| std::env::var_os(var).map(PathBuf::from);
+    let candidates = [
+        env_dir("PULSE_COOKIE"),
+        env_dir("XDG_CONFIG_HOME").map(|dir| dir.join("pulse/cookie")),
+        env_dir("HOME").map(|home| home.join(".config/pulse/cookie")),
+        env_dir("HOME").map(|home| home.join(".pulse-cookie")),
+    ];
+    candidates
+        .into_iter()
+        .flatten()
+        .filter_map(|path| fs::read(path).ok())
+        .find(|cookie| cookie.len() == COOKIE_LENGTH)
+        .unwrap_or_else(|| vec![0; COOKIE_LENGTH])
+}
+
+fn parse_sink_volume(reply: &[u8]) -> io::Result<VolumeInfo> {
+    // Index, name, description, sample spec, channel map and owner module
+    // come before the volume.
+    let mut reader = TagReader::new(reply);
+    reader.u32()?;
+    reader.skip_string()?;
+    reader.skip_string()?;
+    reader.skip_sample_spec()?;
+    reader.skip_channel_map()?;
+    reader.u32()?;
+    let volumes = reader.cvolume()?;
+    let muted = reader.bool()?;
+
+    // The loudest channel, as pavucontrol shows it.
+    let volume = volumes.into_iter().max().unwrap_or_default() as f32 / VOLUME_NORM;
+    Ok(VolumeInfo {
+        volume: volume.clamp(0.0, 1.5),
+        muted,
+    })
+}
+
+pub struct Connection {
+    stream: UnixStream,
+    next_tag: u32,
+}
+
+impl Connection {
+    /// Connects, authenticates and subscribes to sink and server changes.
+    pub fn subscribe() -> io::Result<Self> {
+        let mut conn = Connection {
+            stream: UnixStream::connect(socket_path()?)?,
+            next_tag: 0,
+        };
+
+        let reply = conn.request(
+            COMMAND_AUTH,
+            TagWriter::default()
+                .u32(PROTOCOL_VERSION)
+                .arbitrary(&cookie()),
+        )?;
+        // The upper bits carry shared memory flags.
+        let server_version = TagReader::new(&reply).u32()? & 0xffff;
+        if server_version < MIN_SERVER_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("pulse protocol version {} is too old", server_version),
+            ));
+        }
+
+        conn.request(
+            COMMAND_SET_CLIENT_NAME,
+            TagWriter::default().proplist(&[("application.name", "hypr-panel")]),
+        )?;
+        conn.request(
+            COMMAND_SUBSCRIBE,
+            TagWriter::default().u32(SUBSCRIPTION_MASK_SINK | SUBSCRIPTION_MASK_SERVER),
+        )?;
+        Ok(conn)
+    }
+
+    fn send(&mut self, command: u32, payload: &TagWriter) -> io::Result<u32> {
+        let tag = self.next_tag;
+        self.next_tag = self.next_tag.wrapping_add(1);
+
+        let mut body = TagWriter::default();
+        body.u32(command).u32(tag);
+        body.0.extend_from_slice(&payload.0);
+
+        let mut packet = Vec::with_capacity(20 + body.0.len());
+        for word in [body.0.len() as u32, COMMAND_CHANNEL, 0, 0, 0] {
+            packet.extend_from_slice(&word.to_be_bytes());
+        }
+        packet.extend_from_slice(&body.0);
+        self.stream.write_all(&packet)?;
+        Ok(tag)
+    }
+
+    // Returns the command, its tag and the rest of the payload.
+    fn read_packet(&mut self) -> io::Result<(u32, u32, Vec<u8>)> {
+        loop {
+            let mut descriptor = [0u8; 20];
+            self.stream.read_exact(&mut descriptor)?;
+            let length = u32::from_be_bytes(descriptor[0..4].try_into().unwrap()) as usize;
+            let channel = u32::from_be_bytes(descriptor[4..8].try_into().unwrap());
+
+            let mut payload = vec![0u8; length];
+            self.stream.read_exact(&mut payload)?;
+            // Audio data, which is never asked for.
+            if channel != COMMAND_CHANNEL {
+                continue;
+            }
+
+            let mut reader = TagReader::new(&payload);
+            let command = reader.u32()?;
+            let tag = reader.u32()?;
+            return Ok((command, tag, reader.data.to_vec()));
+        }
+    }
+
+    // Change events that arrive before the reply are already reflected in it,
+    // so they're dropped.
+    fn request(&mut self, command: u32, payload: &TagWriter) -> io::Result<Vec<u8>> {
+        let tag = self.send(command, payload)?;
+        loop {
+            let (command, reply_tag, payload) = self.read_packet()?;
+            match command {
+                COMMAND_REPLY if reply_tag == tag => return Ok(payload),
+                COMMAND_ERROR if reply_tag == tag => {
+                    let code = TagReader::new(&payload).u32().unwrap_or_default();
+                    return Err(io::Error::other(format!("pulse error {}", code)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Blocks until a sink changes or the server does, e.g. by switching its
+    /// default sink.
+    pub fn wait_for_change(&mut self) -> io::Result<()> {
+        loop {
+            let (command, _, payload) = self.read_packet()?;
+            if command != COMMAND_SUBSCRIBE_EVENT {
+                continue;
+            }
+            let event = TagReader::new(&payload).u32()?;
+            if matches!(event & EVENT_FACILITY_MASK, EVENT_SINK | EVENT_SERVER) {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn default_sink_volume(&mut self) -> io::Result<VolumeInfo> {
+        let reply = self.request(
+            COMMAND_GET_SINK_INFO,
+            TagWriter::default()
+                .u32(INVALID_INDEX)
+                .string("@DEFAULT_SINK@"),
+        )?;
+        parse_sink_volume(&reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sink_info(volumes: &[u32], muted: bool) -> Vec<u8> {
+        let mut reply = TagWriter::default();
+        reply
+            .u32(56)
+            .string("alsa_output.analog-stereo")
+            .string("Speakers");
+        // s16le, stereo, 48 kHz.
+        reply.0.extend_from_slice(&[b'a', 3, 2, 0, 0, 0xbb, 0x80]);
+        reply.0.extend_from_slice(&[b'm', 2, 1, 2]);
+        reply.u32(INVALID_INDEX);
+        reply.0.extend_from_slice(&[b'v', volumes.len() as u8]);
+        for volume in volumes {
+            reply.0.extend_from_slice(&volume.to_be_bytes());
+        }
+        reply.0.push(if muted { b'1' } else { b'0' });
+        reply.0
+    }
+
+    #[test]
+    fn reads_the_loudest_channel() {
+        let info = parse_sink_volume(&sink_info(&[0x8000, 0x10000], true)).unwrap();
+        assert_eq!(
+            info,
+            VolumeInfo {
+                volume: 1.0,
+                muted: true
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_replies() {
+        let reply = sink_info(&[0x8000, 0x8000], false);
+        assert!(parse_sink_volume(&reply[..reply.len() - 1]).is_err());
+    }
+}
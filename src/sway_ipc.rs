@@ -0,0 +1,259 @@
+use crate::window_manager::{
+    Action, ActiveWindowInfo, EventHub, WindowManager, WmEvent, Workspace, WorkspaceInfo,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+// i3 IPC, which Sway implements: the magic string, then the payload length
+// and message type as native-endian u32s, then a JSON payload.
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+const EVENT_WORKSPACE: u32 = 0x8000_0000;
+const EVENT_WINDOW: u32 = 0x8000_0003;
+
+fn socket_path() -> io::Result<PathBuf> {
+    std::env::var_os("SWAYSOCK")
+        .map(PathBuf::from)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "SWAYSOCK not set"))
+}
+
+fn write_message(stream: &mut UnixStream, kind: u32, payload: &str) -> io::Result<()> {
+    let mut message = Vec::with_capacity(MAGIC.len() + 8 + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&kind.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    stream.write_all(&message)
+}
+
+fn read_message(stream: &mut UnixStream) -> io::Result<(u32, Value)> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header)?;
+    if &header[..6] != MAGIC {
+        return Err(io::Error::other("invalid i3-ipc reply"));
+    }
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+    let kind = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload)?;
+    let json = serde_json::from_slice(&payload).map_err(io::Error::other)?;
+    Ok((kind, json))
+}
+
+pub fn request(kind: u32, payload: &str) -> io::Result<Value> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    write_message(&mut stream, kind, payload)?;
+    Ok(read_message(&mut stream)?.1)
+}
+
+pub fn run_command(command: &str) -> io::Result<()> {
+    let reply = request(RUN_COMMAND, command)?;
+    let failed = reply
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|result| !result["success"].as_bool().unwrap_or(false));
+    match failed {
+        Some(result) => Err(io::Error::other(
+            result["error"]
+                .as_str()
+                .unwrap_or("command failed")
+                .to_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn children(node: &Value) -> impl Iterator<Item = &Value> {
+    let tiled = node["nodes"].as_array().into_iter().flatten();
+    let floating = node["floating_nodes"].as_array().into_iter().flatten();
+    tiled.chain(floating)
+}
+
+fn is_window(node: &Value) -> bool {
+    matches!(node["type"].as_str(), Some("con" | "floating_con")) && children(node).next().is_none()
+}
+
+fn count_windows(node: &Value) -> u32 {
+    children(node)
+        .map(|child| {
+            if is_window(child) {
+                1
+            } else {
+                count_windows(child)
+            }
+        })
+        .sum()
+}
+
+fn collect_window_counts(node: &Value, counts: &mut HashMap<String, u32>) {
+    if node["type"] == "workspace" {
+        if let Some(name) = node["name"].as_str() {
+            counts.insert(name.to_string(), count_windows(node));
+        }
+        return;
+    }
+    for child in children(node) {
+        collect_window_counts(child, counts);
+    }
+}
+
+fn find_focused(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) {
+        return Some(node);
+    }
+    children(node).find_map(find_focused)
+}
+
+// Wayland windows have an app_id, Xwayland ones only an X11 class.
+fn window_info(node: &Value) -> ActiveWindowInfo {
+    let class = node["app_id"]
+        .as_str()
+        .or_else(|| node["window_properties"]["class"].as_str())
+        .unwrap_or_default();
+    ActiveWindowInfo {
+        class: class.to_string(),
+        title: node["name"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+fn focused_window() -> io::Result<Option<ActiveWindowInfo>> {
+    let tree = request(GET_TREE, "")?;
+    Ok(find_focused(&tree)
+        .filter(|node| is_window(node))
+        .map(window_info))
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+static EVENTS: EventHub<WmEvent> = EventHub::new();
+
+fn handle_event(kind: u32, json: &Value) {
+    match (kind, json["change"].as_str()) {
+        (EVENT_WORKSPACE, _) => EVENTS.broadcast(WmEvent::Workspaces),
+        (EVENT_WINDOW, Some("focus" | "title")) => {
+            let container = &json["container"];
+            if container["focused"].as_bool() == Some(true) {
+                EVENTS.broadcast(WmEvent::ActiveWindow(window_info(container)));
+            }
+        }
+        // Closing the last window on a workspace focuses the workspace
+        // itself, which sends no window event of its own.
+        (EVENT_WINDOW, Some("close")) => {
+            EVENTS.broadcast(WmEvent::Workspaces);
+            if let Ok(window) = focused_window() {
+                EVENTS.broadcast(WmEvent::ActiveWindow(window.unwrap_or_default()));
+            }
+        }
+        (EVENT_WINDOW, _) => EVENTS.broadcast(WmEvent::Workspaces),
+        _ => {}
+    }
+}
+
+fn subscribe_events() -> io::Result<UnixStream> {
+    let mut stream = UnixStream::connect(socket_path()?)?;
+    write_message(&mut stream, SUBSCRIBE, r#"["workspace","window"]"#)?;
+    let (_, reply) = read_message(&mut stream)?;
+    if reply["success"].as_bool() != Some(true) {
+        return Err(io::Error::other("subscription refused"));
+    }
+    Ok(stream)
+}
+
+fn run_event_stream() {
+    EVENTS.run_with_backoff("sway", subscribe_events, |mut stream| {
+        while let Ok((kind, json)) = read_message(&mut stream) {
+            handle_event(kind, &json);
+        }
+    })
+}
+
+pub struct Sway;
+
+impl WindowManager for Sway {
+    fn name(&self) -> &'static str {
+        "Sway"
+    }
+
+    fn workspaces(&self, monitor: Option<&str>) -> io::Result<WorkspaceInfo> {
+        let reply = request(GET_WORKSPACES, "")?;
+        let mut counts = HashMap::new();
+        collect_window_counts(&request(GET_TREE, "")?, &mut counts);
+        let mut info = WorkspaceInfo::default();
+
+        for (i, ws) in reply.as_array().into_iter().flatten().enumerate() {
+            let name = ws["name"].as_str().unwrap_or_default();
+            // Workspaces without a number get -1 from Sway; give each its
+            // own negative id, like Hyprland's named workspaces.
+            let id = match ws["num"].as_i64() {
+                Some(num) if num >= 1 => num as i32,
+                _ => -(i as i32) - 1,
+            };
+            let output = ws["output"].as_str().unwrap_or_default();
+            let flag = |key: &str| ws[key].as_bool().unwrap_or(false);
+
+            let active = match monitor {
+                Some(monitor) => output == monitor && flag("visible"),
+                None => flag("focused"),
+            };
+            if active {
+                info.active = id;
+            }
+            if monitor.is_some_and(|monitor| output != monitor) {
+                continue;
+            }
+            if flag("urgent") {
+                info.urgent.push(id);
+            }
+            info.workspaces.push(Workspace {
+                id,
+                name: name.to_string(),
+                windows: counts.get(name).copied().unwrap_or_default(),
+            });
+        }
+        info.workspaces.sort_unstable_by_key(|ws| ws.id);
+
+        Ok(info)
+    }
+
+    fn focused_window(&self) -> io::Result<Option<ActiveWindowInfo>> {
+        focused_window()
+    }
+
+    fn subscribe(&self) -> async_channel::Receiver<WmEvent> {
+        EVENTS.subscribe(run_event_stream)
+    }
+
+    fn is_connected(&self) -> bool {
+        EVENTS.is_connected()
+    }
+
+    fn dispatch(&self, monitor: Option<&str>, action: &Action) -> io::Result<()> {
+        let command = match action {
+            Action::Workspace(id) => format!("workspace number {}", id),
+            Action::NamedWorkspace(name) => format!("workspace {}", quote(name)),
+            Action::ToggleSpecial(_) => "scratchpad show".to_string(),
+            Action::RelativeWorkspace(delta) => {
+                let step = if *delta > 0 {
+                    "workspace next_on_output"
+                } else {
+                    "workspace prev_on_output"
+                };
+                vec![step; delta.unsigned_abs() as usize].join("; ")
+            }
+        };
+        match monitor {
+            Some(monitor) => run_command(&format!("focus output {}; {}", quote(monitor), command)),
+            None => run_command(&command),
+        }
+    }
+}
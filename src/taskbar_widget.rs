@@ -0,0 +1,143 @@
+use crate::desktop_entries;
+use crate::hyprland_ipc::{self, Client};
+use crate::hyprland_listener::TaskbarInfo;
+use crate::window_manager;
+use gtk4::prelude::*;
+use gtk4::{Box as GBox, GestureClick, Image, Label, Orientation};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskbarFilter {
+    All,
+    Monitor,
+    Workspace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskbarOptions {
+    pub filter: TaskbarFilter,
+    pub group: bool,
+    pub max_width: i32,
+    pub icon_size: i32,
+}
+
+fn visible_clients(info: &TaskbarInfo, filter: TaskbarFilter) -> Vec<&Client> {
+    let mut clients: Vec<&Client> = info
+        .clients
+        .iter()
+        .filter(|c| match filter {
+            TaskbarFilter::All => true,
+            TaskbarFilter::Monitor => info.monitor_id == Some(c.monitor),
+            TaskbarFilter::Workspace => info.active_workspace == Some(c.workspace.id),
+        })
+        .collect();
+    clients.sort_by_key(|c| c.workspace.id);
+    clients
+}
+
+// Groups keep the position of their first window.
+fn group_clients(clients: Vec<&Client>, group: bool) -> Vec<Vec<&Client>> {
+    if !group {
+        return clients.into_iter().map(|c| vec![c]).collect();
+    }
+    let mut groups: Vec<Vec<&Client>> = Vec::new();
+    for client in clients {
+        match groups.iter_mut().find(|g| g[0].class == client.class) {
+            Some(group) => group.push(client),
+            None => groups.push(vec![client]),
+        }
+    }
+    groups
+}
+
+fn build_entry(group: &[&Client], focused: Option<&str>, options: &TaskbarOptions) -> GBox {
+    let focused_index = group
+        .iter()
+        .position(|c| Some(c.address.as_str()) == focused);
+    let recent = group
+        .iter()
+        .min_by_key(|c| c.focus_history_id)
+        .copied()
+        .unwrap_or(group[0]);
+
+    let entry = GBox::new(Orientation::Horizontal, 4);
+    entry.add_css_class("task");
+    if focused_index.is_some() {
+        entry.add_css_class("focused");
+    }
+    entry.set_tooltip_text(Some(&recent.title));
+
+    let icon = Image::new();
+    icon.set_pixel_size(options.icon_size);
+    if desktop_entries::set_app_icon(&icon, &recent.class) {
+        entry.append(&icon);
+    }
+
+    if options.max_width > 0 {
+        let title = Label::new(Some(&recent.title));
+        title.set_max_width_chars(options.max_width);
+        title.set_ellipsize(pango::EllipsizeMode::End);
+        entry.append(&title);
+    }
+
+    if group.len() > 1 {
+        let count = Label::new(Some(&group.len().to_string()));
+        count.add_css_class("count");
+        entry.append(&count);
+    }
+
+    // Clicking a group that already has focus cycles through its windows.
+    let focus_target = match focused_index {
+        Some(i) if group.len() > 1 => group[(i + 1) % group.len()].address.clone(),
+        _ => recent.address.clone(),
+    };
+    let click = GestureClick::new();
+    click.set_button(gtk4::gdk::BUTTON_PRIMARY);
+    click.connect_released(move |gesture, _n_press, _x, _y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        window_manager::spawn_request(
+            format!("dispatch focuswindow address:{}", focus_target),
+            |command| hyprland_ipc::command(command),
+        );
+    });
+    entry.add_controller(click);
+
+    let close_target = recent.address.clone();
+    let middle_click = GestureClick::new();
+    middle_click.set_button(gtk4::gdk::BUTTON_MIDDLE);
+    middle_click.connect_released(move |gesture, _n_press, _x, _y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        window_manager::spawn_request(
+            format!("dispatch closewindow address:{}", close_target),
+            |command| hyprland_ipc::command(command),
+        );
+    });
+    entry.add_controller(middle_click);
+
+    entry
+}
+
+pub fn build_taskbar_widget(
+    orientation: Orientation,
+    options: TaskbarOptions,
+) -> (GBox, impl Fn(TaskbarInfo)) {
+    let container = GBox::new(orientation, 4);
+    container.add_css_class("taskbar");
+
+    // Weak for the same reason as the workspace indicator's updater.
+    let container_weak = container.downgrade();
+    let updater = move |info: TaskbarInfo| {
+        let Some(container) = container_weak.upgrade() else {
+            return;
+        };
+        while let Some(child) = container.first_child() {
+            container.remove(&child);
+        }
+
+        let clients = visible_clients(&info, options.filter);
+        for group in group_clients(clients, options.group) {
+            container.append(&build_entry(&group, info.focused.as_deref(), &options));
+        }
+    };
+
+    (container, updater)
+}
@@ -1,6 +1,9 @@
+use crate::pulse_ipc;
+use crate::window_manager::{EventHub, HubEvent};
 use async_channel;
 use evdev::{Device, EventSummary, KeyCode};
 use std::process::Command;
+use std::sync::Mutex;
 use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -32,6 +35,35 @@ fn get_volume_info() -> Option<VolumeInfo> {
     Some(VolumeInfo { volume: volume.clamp(0.0, 1.5), muted })
 }
 
+// Runs wpctl from a helper thread so GTK callbacks never wait on it.
+fn spawn_wpctl(args: Vec<String>) {
+    std::thread::spawn(move || {
+        if let Err(e) = Command::new("wpctl").args(&args).status() {
+            eprintln!("[volume] Failed to run wpctl {:?}: {}", args, e);
+        }
+    });
+}
+
+/// Changes the default sink's volume by `percent`, capped at 100%.
+pub fn change_volume(percent: i64) {
+    let sign = if percent < 0 { '-' } else { '+' };
+    spawn_wpctl(vec![
+        "set-volume".into(),
+        "-l".into(),
+        "1.0".into(),
+        "@DEFAULT_AUDIO_SINK@".into(),
+        format!("{}%{}", percent.unsigned_abs(), sign),
+    ]);
+}
+
+pub fn toggle_mute() {
+    spawn_wpctl(vec![
+        "set-mute".into(),
+        "@DEFAULT_AUDIO_SINK@".into(),
+        "toggle".into(),
+    ]);
+}
+
 const VOLUME_KEYS: [KeyCode; 3] = [
     KeyCode::KEY_VOLUMEUP,
     KeyCode::KEY_VOLUMEDOWN,
@@ -106,4 +138,55 @@ pub fn start_volume_listener() -> async_channel::Receiver<VolumeEvent> {
     }
 
     receiver
-}
\ No newline at end of file
+}
+
+/// A reading of the default sink. `initial` marks the reading a listener
+/// starts with, which isn't a change.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VolumeUpdate {
+    pub info:    VolumeInfo,
+    pub initial: bool,
+}
+
+impl HubEvent for VolumeUpdate {
+    fn connection(_connected: bool) -> Option<Self> {
+        None
+    }
+}
+
+static EVENTS: EventHub<VolumeUpdate> = EventHub::new();
+static CURRENT: Mutex<Option<VolumeInfo>> = Mutex::new(None);
+
+fn publish(info: VolumeInfo) {
+    let mut current = CURRENT.lock().unwrap();
+    if current.as_ref() == Some(&info) {
+        return;
+    }
+    let initial = current.is_none();
+    *current = Some(info.clone());
+    EVENTS.broadcast(VolumeUpdate { info, initial });
+}
+
+fn run_event_stream() {
+    EVENTS.run_with_backoff("volume", pulse_ipc::Connection::subscribe, |mut conn| {
+        loop {
+            match conn.default_sink_volume() {
+                Ok(info) => publish(info),
+                Err(e) => eprintln!("[volume] Failed to read the default sink: {}", e),
+            }
+            if conn.wait_for_change().is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Sends the default sink's volume whenever it changes, whatever changed it,
+/// starting with the current reading. Every listener shares one subscription
+/// to the pulse server (PulseAudio, or PipeWire's pipewire-pulse).
+pub fn start_volume_info_listener() -> async_channel::Receiver<VolumeUpdate> {
+    // Held while subscribing so no change slips in between.
+    let current = CURRENT.lock().unwrap();
+    let first = current.clone().map(|info| VolumeUpdate { info, initial: true });
+    EVENTS.subscribe_from(run_event_stream, first)
+}
@@ -0,0 +1,510 @@
+use crate::window_manager::{
+    Action, ActiveWindowInfo, EventHub, WindowManager, WmEvent, Workspace, WorkspaceInfo,
+};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+use wayland_client::backend::ObjectId;
+use wayland_client::globals::{GlobalListContents, registry_queue_init};
+use wayland_client::protocol::{wl_output, wl_registry};
+use wayland_client::{Connection, Dispatch, EventQueue, Proxy, QueueHandle, event_created_child};
+use wayland_protocols::ext::workspace::v1::client::{
+    ext_workspace_group_handle_v1::{self, ExtWorkspaceGroupHandleV1},
+    ext_workspace_handle_v1::{self, ExtWorkspaceHandleV1},
+    ext_workspace_manager_v1::{self, ExtWorkspaceManagerV1},
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+// `wl_output.name` was added in version 4.
+const OUTPUT_VERSION: u32 = 4;
+// Index of `activated` in zwlr_foreign_toplevel_handle_v1's state enum.
+const TOPLEVEL_ACTIVATED: u32 = 2;
+
+#[derive(Debug)]
+struct WorkspaceEntry {
+    name: String,
+    coordinates: Vec<u32>,
+    state: ext_workspace_handle_v1::State,
+}
+
+impl Default for WorkspaceEntry {
+    fn default() -> Self {
+        WorkspaceEntry {
+            name: String::new(),
+            coordinates: vec![],
+            state: ext_workspace_handle_v1::State::empty(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Group {
+    outputs: Vec<ObjectId>,
+    workspaces: Vec<ObjectId>,
+}
+
+#[derive(Debug, Default)]
+struct Toplevel {
+    app_id: String,
+    title: String,
+    activated: bool,
+}
+
+// Owned by the event thread and updated as the compositor's events arrive.
+#[derive(Default)]
+struct State {
+    outputs: HashMap<ObjectId, String>,
+    groups: HashMap<ObjectId, Group>,
+    workspaces: Vec<(ExtWorkspaceHandleV1, WorkspaceEntry)>,
+    toplevels: HashMap<ObjectId, Toplevel>,
+    focused: Option<ActiveWindowInfo>,
+    workspaces_published: bool,
+}
+
+// What the other threads see, published after each atomic update.
+#[derive(Clone)]
+struct SnapshotWorkspace {
+    handle: ExtWorkspaceHandleV1,
+    id: i32,
+    name: String,
+    outputs: Vec<String>,
+    active: bool,
+    urgent: bool,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    connection: Option<Connection>,
+    manager: Option<ExtWorkspaceManagerV1>,
+    workspaces: Vec<SnapshotWorkspace>,
+    focused: Option<ActiveWindowInfo>,
+}
+
+static EVENTS: EventHub<WmEvent> = EventHub::new();
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+fn entry_mut<'a>(
+    state: &'a mut State,
+    handle: &ExtWorkspaceHandleV1,
+) -> Option<&'a mut WorkspaceEntry> {
+    state
+        .workspaces
+        .iter_mut()
+        .find(|(h, _)| h == handle)
+        .map(|(_, entry)| entry)
+}
+
+impl State {
+    fn publish_workspaces(&mut self) {
+        self.workspaces_published = true;
+        let mut ordered: Vec<&(ExtWorkspaceHandleV1, WorkspaceEntry)> =
+            self.workspaces.iter().collect();
+        ordered.sort_by(|a, b| a.1.coordinates.cmp(&b.1.coordinates));
+
+        let workspaces = ordered
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (_, entry))| !entry.state.contains(ext_workspace_handle_v1::State::Hidden))
+            .map(|(i, (handle, entry))| {
+                let outputs = self
+                    .groups
+                    .values()
+                    .filter(|group| group.workspaces.contains(&handle.id()))
+                    .flat_map(|group| group.outputs.iter())
+                    .filter_map(|output| self.outputs.get(output).cloned())
+                    .collect();
+                SnapshotWorkspace {
+                    handle: handle.clone(),
+                    // Numeric names map onto the numbered slots; everything
+                    // else gets its own negative id, like Hyprland's named
+                    // workspaces.
+                    id: entry
+                        .name
+                        .parse()
+                        .ok()
+                        .filter(|id| *id >= 1)
+                        .unwrap_or(-(i as i32) - 1),
+                    name: entry.name.clone(),
+                    outputs,
+                    active: entry.state.contains(ext_workspace_handle_v1::State::Active),
+                    urgent: entry.state.contains(ext_workspace_handle_v1::State::Urgent),
+                }
+            })
+            .collect();
+
+        if let Some(snapshot) = SNAPSHOT.lock().unwrap().as_mut() {
+            snapshot.workspaces = workspaces;
+        }
+        EVENTS.broadcast(WmEvent::Workspaces);
+    }
+
+    fn publish_focus(&mut self) {
+        let focused = self
+            .toplevels
+            .values()
+            .find(|toplevel| toplevel.activated)
+            .map(|toplevel| ActiveWindowInfo {
+                class: toplevel.app_id.clone(),
+                title: toplevel.title.clone(),
+            });
+        if focused == self.focused {
+            return;
+        }
+
+        self.focused = focused.clone();
+        if let Some(snapshot) = SNAPSHOT.lock().unwrap().as_mut() {
+            snapshot.focused = focused.clone();
+        }
+        EVENTS.broadcast(WmEvent::ActiveWindow(focused.unwrap_or_default()));
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        // Outputs plugged in later.
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+            && interface == wl_output::WlOutput::interface().name
+            && version >= OUTPUT_VERSION
+        {
+            registry.bind::<wl_output::WlOutput, _, _>(name, OUTPUT_VERSION, qh, ());
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, ()> for State {
+    fn event(
+        state: &mut Self,
+        output: &wl_output::WlOutput,
+        event: wl_output::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_output::Event::Name { name } => {
+                state.outputs.insert(output.id(), name);
+            }
+            // A hotplugged output can enter a workspace group before its
+            // name arrives.
+            wl_output::Event::Done if state.workspaces_published => state.publish_workspaces(),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtWorkspaceManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _manager: &ExtWorkspaceManagerV1,
+        event: ext_workspace_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_workspace_manager_v1::Event::WorkspaceGroup { workspace_group } => {
+                state.groups.insert(workspace_group.id(), Group::default());
+            }
+            ext_workspace_manager_v1::Event::Workspace { workspace } => {
+                state
+                    .workspaces
+                    .push((workspace, WorkspaceEntry::default()));
+            }
+            ext_workspace_manager_v1::Event::Done => state.publish_workspaces(),
+            _ => {}
+        }
+    }
+
+    event_created_child!(State, ExtWorkspaceManagerV1, [
+        ext_workspace_manager_v1::EVT_WORKSPACE_GROUP_OPCODE => (ExtWorkspaceGroupHandleV1, ()),
+        ext_workspace_manager_v1::EVT_WORKSPACE_OPCODE => (ExtWorkspaceHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ExtWorkspaceGroupHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ExtWorkspaceGroupHandleV1,
+        event: ext_workspace_group_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use ext_workspace_group_handle_v1::Event;
+        if let Event::Removed = event {
+            state.groups.remove(&handle.id());
+            handle.destroy();
+            return;
+        }
+        let Some(group) = state.groups.get_mut(&handle.id()) else {
+            return;
+        };
+        match event {
+            Event::OutputEnter { output } => group.outputs.push(output.id()),
+            Event::OutputLeave { output } => group.outputs.retain(|id| *id != output.id()),
+            Event::WorkspaceEnter { workspace } => group.workspaces.push(workspace.id()),
+            Event::WorkspaceLeave { workspace } => {
+                group.workspaces.retain(|id| *id != workspace.id())
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtWorkspaceHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ExtWorkspaceHandleV1,
+        event: ext_workspace_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use ext_workspace_handle_v1::Event;
+        if let Event::Removed = event {
+            state.workspaces.retain(|(h, _)| h != handle);
+            handle.destroy();
+            return;
+        }
+        let Some(entry) = entry_mut(state, handle) else {
+            return;
+        };
+        match event {
+            Event::Name { name } => entry.name = name,
+            Event::Coordinates { coordinates } => {
+                entry.coordinates = coordinates
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+            }
+            Event::State { state } => {
+                entry.state = state
+                    .into_result()
+                    .unwrap_or(ext_workspace_handle_v1::State::empty());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _manager: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } = event {
+            state.toplevels.insert(toplevel.id(), Toplevel::default());
+        }
+    }
+
+    event_created_child!(State, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        use zwlr_foreign_toplevel_handle_v1::Event;
+        match event {
+            Event::Done => state.publish_focus(),
+            Event::Closed => {
+                state.toplevels.remove(&handle.id());
+                handle.destroy();
+                state.publish_focus();
+            }
+            event => {
+                let Some(toplevel) = state.toplevels.get_mut(&handle.id()) else {
+                    return;
+                };
+                match event {
+                    Event::Title { title } => toplevel.title = title,
+                    Event::AppId { app_id } => toplevel.app_id = app_id,
+                    Event::State { state } => {
+                        toplevel.activated = state.chunks_exact(4).any(|c| {
+                            u32::from_ne_bytes([c[0], c[1], c[2], c[3]]) == TOPLEVEL_ACTIVATED
+                        });
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn connect() -> io::Result<(EventQueue<State>, State)> {
+    let conn = Connection::connect_to_env().map_err(io::Error::other)?;
+    let (globals, mut queue) = registry_queue_init::<State>(&conn).map_err(io::Error::other)?;
+    let qh = queue.handle();
+    let mut state = State::default();
+
+    // Output names have to be known before the first workspace update, or
+    // per-monitor panels would find none of their workspaces in it.
+    for global in globals.contents().clone_list() {
+        if global.interface == wl_output::WlOutput::interface().name
+            && global.version >= OUTPUT_VERSION
+        {
+            globals.registry().bind::<wl_output::WlOutput, _, _>(
+                global.name,
+                OUTPUT_VERSION,
+                &qh,
+                (),
+            );
+        }
+    }
+    queue.roundtrip(&mut state).map_err(io::Error::other)?;
+
+    let manager = globals
+        .bind::<ExtWorkspaceManagerV1, _, _>(&qh, 1..=1, ())
+        .ok();
+    let toplevels = globals
+        .bind::<ZwlrForeignToplevelManagerV1, _, _>(&qh, 1..=3, ())
+        .ok();
+    if manager.is_none() && toplevels.is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "compositor supports neither ext-workspace-v1 nor wlr-foreign-toplevel-management",
+        ));
+    }
+
+    *SNAPSHOT.lock().unwrap() = Some(Snapshot {
+        connection: Some(conn),
+        manager,
+        ..Snapshot::default()
+    });
+    Ok((queue, state))
+}
+
+fn run_event_stream() {
+    EVENTS.run_with_backoff("wayland", connect, |(mut queue, mut state)| {
+        loop {
+            if let Err(e) = queue.blocking_dispatch(&mut state) {
+                eprintln!("[wayland] {}", e);
+                break;
+            }
+        }
+        *SNAPSHOT.lock().unwrap() = None;
+    })
+}
+
+/// Works on any compositor with `ext-workspace-v1` and/or
+/// `wlr-foreign-toplevel-management`. Neither protocol says how many windows
+/// a workspace holds, so workspaces are never marked as occupied.
+pub struct Wayland;
+
+impl WindowManager for Wayland {
+    fn name(&self) -> &'static str {
+        "Wayland"
+    }
+
+    fn workspaces(&self, monitor: Option<&str>) -> io::Result<WorkspaceInfo> {
+        let mut info = WorkspaceInfo::default();
+        // Nothing is known until the first roundtrip; its events follow.
+        let snapshot = SNAPSHOT.lock().unwrap();
+        let Some(snapshot) = snapshot.as_ref() else {
+            return Ok(info);
+        };
+
+        for ws in &snapshot.workspaces {
+            if monitor.is_some_and(|monitor| !ws.outputs.iter().any(|o| o == monitor)) {
+                continue;
+            }
+            if ws.active {
+                info.active = ws.id;
+            }
+            if ws.urgent {
+                info.urgent.push(ws.id);
+            }
+            info.workspaces.push(Workspace {
+                id: ws.id,
+                name: ws.name.clone(),
+                windows: 0,
+            });
+        }
+        info.workspaces.sort_unstable_by_key(|ws| ws.id);
+
+        Ok(info)
+    }
+
+    fn focused_window(&self) -> io::Result<Option<ActiveWindowInfo>> {
+        let snapshot = SNAPSHOT.lock().unwrap();
+        Ok(snapshot.as_ref().and_then(|s| s.focused.clone()))
+    }
+
+    fn subscribe(&self) -> async_channel::Receiver<WmEvent> {
+        EVENTS.subscribe(run_event_stream)
+    }
+
+    fn is_connected(&self) -> bool {
+        EVENTS.is_connected()
+    }
+
+    fn dispatch(&self, monitor: Option<&str>, action: &Action) -> io::Result<()> {
+        let snapshot = SNAPSHOT.lock().unwrap();
+        let (Some(snapshot), Some(manager)) = (
+            snapshot.as_ref(),
+            snapshot.as_ref().and_then(|s| s.manager.as_ref()),
+        ) else {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "ext-workspace-v1 is not available",
+            ));
+        };
+
+        let on_monitor: Vec<&SnapshotWorkspace> = snapshot
+            .workspaces
+            .iter()
+            .filter(|ws| monitor.is_none_or(|m| ws.outputs.iter().any(|o| o == m)))
+            .collect();
+        let target = match action {
+            Action::Workspace(id) => on_monitor.iter().find(|ws| ws.id == *id),
+            Action::NamedWorkspace(name) => on_monitor.iter().find(|ws| &ws.name == name),
+            Action::RelativeWorkspace(_) if on_monitor.is_empty() => None,
+            Action::RelativeWorkspace(delta) => {
+                let active = on_monitor.iter().position(|ws| ws.active).unwrap_or(0) as i32;
+                let index = (active + delta).clamp(0, on_monitor.len() as i32 - 1);
+                on_monitor.get(index as usize)
+            }
+            Action::ToggleSpecial(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "there are no special workspaces",
+                ));
+            }
+        };
+        let Some(target) = target else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no such workspace"));
+        };
+
+        target.handle.activate();
+        manager.commit();
+        if let Some(conn) = &snapshot.connection {
+            conn.flush().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,8 @@
+use crate::window_manager::{EventHub, HubEvent};
 use async_channel;
-use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct WifiInfo {
@@ -42,38 +44,50 @@ fn get_wifi_info() -> WifiInfo {
     }
 }
 
-pub fn start_wifi_listener() -> async_channel::Receiver<WifiInfo> {
-    let (sender, receiver) = async_channel::unbounded();
+impl HubEvent for WifiInfo {
+    fn connection(_connected: bool) -> Option<Self> {
+        None
+    }
+}
 
-    let _ = sender.send_blocking(get_wifi_info());
+static EVENTS: EventHub<WifiInfo> = EventHub::new();
+static CURRENT: Mutex<Option<WifiInfo>> = Mutex::new(None);
 
-    std::thread::spawn(move || {
-        let mut child = match Command::new("nmcli")
-            .arg("monitor")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-        {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("[wifi] Failed to spawn nmcli monitor: {}", e);
-                return;
-            }
-        };
+fn publish(info: WifiInfo) {
+    let mut current = CURRENT.lock().unwrap();
+    if current.as_ref() != Some(&info) {
+        *current = Some(info.clone());
+        EVENTS.broadcast(info);
+    }
+}
 
-        let stdout = child.stdout.take().expect("nmcli monitor has no stdout");
-        let reader = BufReader::new(stdout);
+fn spawn_monitor() -> io::Result<Child> {
+    Command::new("nmcli")
+        .arg("monitor")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+}
 
-        for line in reader.lines() {
-            if let Ok(line) = line {
-                if line.contains("connected") || line.contains("disconnected") {
-                    let _ = sender.send_blocking(get_wifi_info());
-                }
+fn run_event_stream() {
+    EVENTS.run_with_backoff("wifi", spawn_monitor, |mut child| {
+        // Anything may have changed while nmcli wasn't running.
+        publish(get_wifi_info());
+
+        let stdout = child.stdout.take().expect("nmcli monitor has no stdout");
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line.contains("connected") || line.contains("disconnected") {
+                publish(get_wifi_info());
             }
         }
-
         let _ = child.wait();
-    });
+    })
+}
 
-    receiver
-}
\ No newline at end of file
+/// Every listener shares one `nmcli monitor`, and starts from the current
+/// state once it's known.
+pub fn start_wifi_listener() -> async_channel::Receiver<WifiInfo> {
+    // Held while subscribing so no change slips in between.
+    let current = CURRENT.lock().unwrap();
+    EVENTS.subscribe_from(run_event_stream, current.clone())
+}
@@ -0,0 +1,360 @@
+use crate::hyprland_backend::Hyprland;
+use crate::sway_ipc::Sway;
+use crate::wayland_backend::Wayland;
+use std::fmt;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workspace {
+    pub id: i32,
+    pub name: String,
+    pub windows: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceInfo {
+    pub active: i32,
+    pub workspaces: Vec<Workspace>,
+    pub special: Option<Workspace>,
+    pub urgent: Vec<i32>,
+}
+
+impl Default for WorkspaceInfo {
+    fn default() -> Self {
+        WorkspaceInfo {
+            active: 1,
+            workspaces: vec![],
+            special: None,
+            urgent: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActiveWindowInfo {
+    pub class: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WmEvent {
+    /// Workspaces or the windows on them changed; query them again.
+    Workspaces,
+    ActiveWindow(ActiveWindowInfo),
+    /// A window on this workspace asked for attention.
+    Urgent(i32),
+    Connected,
+    Disconnected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    Workspace(i32),
+    NamedWorkspace(String),
+    /// Hyprland's special workspace, Sway's scratchpad.
+    ToggleSpecial(Option<String>),
+    /// Moves by this many workspaces on the monitor.
+    RelativeWorkspace(i32),
+}
+
+pub trait WindowManager: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Workspaces on `monitor`, or on all monitors when `None`. `special` and
+    /// `active` then follow the focused monitor.
+    fn workspaces(&self, monitor: Option<&str>) -> io::Result<WorkspaceInfo>;
+
+    /// Returns `None` when no window is focused.
+    fn focused_window(&self) -> io::Result<Option<ActiveWindowInfo>>;
+
+    fn subscribe(&self) -> async_channel::Receiver<WmEvent>;
+
+    fn is_connected(&self) -> bool;
+
+    /// Runs `action`, on `monitor` first being focused when given.
+    fn dispatch(&self, monitor: Option<&str>, action: &Action) -> io::Result<()>;
+}
+
+/// Events an `EventHub` can carry. `connection` is what subscribers are sent
+/// when the hub's connection comes or goes, if anything.
+pub trait HubEvent: Clone + Send + 'static {
+    fn connection(connected: bool) -> Option<Self>;
+}
+
+impl HubEvent for WmEvent {
+    fn connection(connected: bool) -> Option<Self> {
+        Some(if connected {
+            WmEvent::Connected
+        } else {
+            WmEvent::Disconnected
+        })
+    }
+}
+
+/// Fans events from a backend's single event connection out to every
+/// subscriber, and tracks whether that connection is up.
+pub struct EventHub<E> {
+    subscribers: Mutex<Vec<async_channel::Sender<E>>>,
+    start: Once,
+    // Assumed until the first connection attempt says otherwise, so modules
+    // don't flash their disconnected state on startup.
+    connected: AtomicBool,
+}
+
+impl<E: HubEvent> EventHub<E> {
+    pub const fn new() -> Self {
+        EventHub {
+            subscribers: Mutex::new(Vec::new()),
+            start: Once::new(),
+            connected: AtomicBool::new(true),
+        }
+    }
+
+    /// `run` is started on its own thread by the first subscriber.
+    pub fn subscribe(&self, run: fn()) -> async_channel::Receiver<E> {
+        self.subscribe_from(run, None)
+    }
+
+    /// Like `subscribe`, with `first` queued ahead of any broadcast.
+    pub fn subscribe_from(&self, run: fn(), first: Option<E>) -> async_channel::Receiver<E> {
+        let (sender, receiver) = async_channel::unbounded();
+        if let Some(event) = first {
+            let _ = sender.send_blocking(event);
+        }
+        self.subscribers.lock().unwrap().push(sender);
+        self.start.call_once(|| {
+            std::thread::spawn(run);
+        });
+        receiver
+    }
+
+    pub fn broadcast(&self, event: E) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|sender| sender.send_blocking(event.clone()).is_ok());
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    pub fn set_connected(&self, connected: bool) {
+        if self.connected.swap(connected, Ordering::SeqCst) != connected
+            && let Some(event) = E::connection(connected)
+        {
+            self.broadcast(event);
+        }
+    }
+
+    /// Keeps an event connection alive: `connect` opens it, `run` reads from
+    /// it until it drops. Failed attempts back off exponentially.
+    pub fn run_with_backoff<S>(
+        &self,
+        name: &str,
+        connect: impl Fn() -> io::Result<S>,
+        run: impl Fn(S),
+    ) -> ! {
+        const MIN_RETRY_DELAY: Duration = Duration::from_millis(500);
+        const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+        let mut delay = MIN_RETRY_DELAY;
+        loop {
+            match connect() {
+                Ok(session) => {
+                    self.set_connected(true);
+                    let started = Instant::now();
+                    run(session);
+                    eprintln!("[{}] Event connection closed, reconnecting", name);
+                    self.set_connected(false);
+                    // One that drops straight away counts as a failed attempt,
+                    // so e.g. a helper process that exits isn't respawned in a
+                    // tight loop.
+                    if started.elapsed() >= MAX_RETRY_DELAY {
+                        delay = MIN_RETRY_DELAY;
+                    }
+                    std::thread::sleep(delay);
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+                Err(e) => {
+                    if self.is_connected() || delay == MAX_RETRY_DELAY {
+                        eprintln!("[{}] Failed to connect: {}", name, e);
+                    }
+                    self.set_connected(false);
+                    std::thread::sleep(delay);
+                    delay = (delay * 2).min(MAX_RETRY_DELAY);
+                }
+            }
+        }
+    }
+}
+
+fn detect() -> Box<dyn WindowManager> {
+    let set = |var: &str| std::env::var_os(var).is_some();
+
+    match std::env::var("HYPR_PANEL_BACKEND").as_deref() {
+        Ok("hyprland") => return Box::new(Hyprland),
+        Ok("sway") => return Box::new(Sway),
+        Ok("wayland") => return Box::new(Wayland),
+        Ok(other) => eprintln!("[wm] Unknown HYPR_PANEL_BACKEND {:?}, detecting", other),
+        Err(_) => {}
+    }
+
+    if set("HYPRLAND_INSTANCE_SIGNATURE") {
+        Box::new(Hyprland)
+    } else if set("SWAYSOCK") {
+        Box::new(Sway)
+    } else if set("WAYLAND_DISPLAY") {
+        Box::new(Wayland)
+    } else {
+        Box::new(Hyprland)
+    }
+}
+
+/// The window manager the panel runs under, picked from the environment.
+/// `HYPR_PANEL_BACKEND` (hyprland, sway or wayland) overrides the guess.
+pub fn current() -> &'static dyn WindowManager {
+    static CURRENT: OnceLock<Box<dyn WindowManager>> = OnceLock::new();
+    CURRENT
+        .get_or_init(|| {
+            let wm = detect();
+            eprintln!("[wm] Using the {} backend", wm.name());
+            wm
+        })
+        .as_ref()
+}
+
+/// Whether Hyprland-only features, which talk to its IPC directly, can run.
+pub fn is_hyprland() -> bool {
+    current().name() == Hyprland.name()
+}
+
+/// Runs `request` on `what` from a helper thread so GTK callbacks never block
+/// on a socket. Failures are only logged.
+pub fn spawn_request<T: fmt::Debug + Send + 'static>(
+    what: T,
+    request: impl FnOnce(&T) -> io::Result<()> + Send + 'static,
+) {
+    std::thread::spawn(move || {
+        if let Err(e) = request(&what) {
+            eprintln!("[wm] {:?} failed: {}", what, e);
+        }
+    });
+}
+
+pub fn spawn_dispatch(monitor: Option<String>, action: Action) {
+    spawn_request(action, move |action| {
+        current().dispatch(monitor.as_deref(), action)
+    });
+}
+
+fn focused_window() -> ActiveWindowInfo {
+    match current().focused_window() {
+        Ok(window) => window.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("[wm] Failed to query the focused window: {}", e);
+            ActiveWindowInfo::default()
+        }
+    }
+}
+
+pub fn start_active_window_listener() -> async_channel::Receiver<ActiveWindowInfo> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = current().subscribe();
+
+    std::thread::spawn(move || {
+        if sender.send_blocking(focused_window()).is_err() {
+            return;
+        }
+
+        while let Ok(event) = events.recv_blocking() {
+            let info = match event {
+                WmEvent::ActiveWindow(info) => info,
+                // The window focused before a restart is most likely gone.
+                WmEvent::Connected => focused_window(),
+                WmEvent::Disconnected => ActiveWindowInfo::default(),
+                _ => continue,
+            };
+            if sender.send_blocking(info).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+fn workspace_info(monitor: Option<&str>) -> WorkspaceInfo {
+    current().workspaces(monitor).unwrap_or_else(|e| {
+        eprintln!("[wm] Failed to query workspaces: {}", e);
+        WorkspaceInfo::default()
+    })
+}
+
+pub fn start_workspace_listener(monitor: Option<String>) -> async_channel::Receiver<WorkspaceInfo> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = current().subscribe();
+
+    std::thread::spawn(move || {
+        if sender
+            .send_blocking(workspace_info(monitor.as_deref()))
+            .is_err()
+        {
+            return;
+        }
+
+        // Urgent workspaces stay marked until they are visited.
+        let mut urgent: Vec<i32> = Vec::new();
+
+        while let Ok(event) = events.recv_blocking() {
+            match event {
+                WmEvent::Urgent(id) => {
+                    if !urgent.contains(&id) {
+                        urgent.push(id);
+                    }
+                }
+                WmEvent::Workspaces | WmEvent::Connected => {}
+                _ => continue,
+            }
+
+            let mut info = workspace_info(monitor.as_deref());
+            urgent.retain(|id| *id != info.active && info.workspaces.iter().any(|ws| ws.id == *id));
+            for id in &urgent {
+                if !info.urgent.contains(id) {
+                    info.urgent.push(*id);
+                }
+            }
+            if sender.send_blocking(info).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// Sends whether the window manager is currently reachable.
+pub fn start_connection_listener() -> async_channel::Receiver<bool> {
+    let (sender, receiver) = async_channel::unbounded();
+    let events = current().subscribe();
+
+    std::thread::spawn(move || {
+        if sender.send_blocking(current().is_connected()).is_err() {
+            return;
+        }
+
+        while let Ok(event) = events.recv_blocking() {
+            let connected = match event {
+                WmEvent::Connected => true,
+                WmEvent::Disconnected => false,
+                _ => continue,
+            };
+            if sender.send_blocking(connected).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
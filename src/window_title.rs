@@ -0,0 +1,207 @@
+use crate::config::ModuleConfig;
+use crate::window_manager::ActiveWindowInfo;
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TitleMode {
+    Class,
+    Title,
+    Both,
+}
+
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    pub class: Option<Regex>,
+    pub pattern: Regex,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TitleFormat {
+    pub mode: TitleMode,
+    pub placeholder: String,
+    pub rules: Vec<RewriteRule>,
+}
+
+fn compile(pattern: &str) -> Option<Regex> {
+    match Regex::new(pattern) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            eprintln!("[active_window] Invalid rewrite regex {:?}: {}", pattern, e);
+            None
+        }
+    }
+}
+
+fn parse_rule(value: &toml::Value) -> Option<RewriteRule> {
+    let table = value.as_table()?;
+    let class = match table.get("class").and_then(|v| v.as_str()) {
+        Some(class) => Some(compile(class)?),
+        None => None,
+    };
+    Some(RewriteRule {
+        class,
+        pattern: compile(table.get("match")?.as_str()?)?,
+        replacement: table
+            .get("replace")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+impl TitleFormat {
+    pub fn from_config(module: &ModuleConfig) -> Self {
+        let mode = match module.str("show") {
+            None | Some("class") => TitleMode::Class,
+            Some("title") => TitleMode::Title,
+            Some("both") => TitleMode::Both,
+            Some(other) => {
+                eprintln!("[active_window] Unknown show mode {:?}, using class", other);
+                TitleMode::Class
+            }
+        };
+        let rules = module
+            .options
+            .get("rewrite")
+            .and_then(|v| v.as_array())
+            .map(|rules| rules.iter().filter_map(parse_rule).collect())
+            .unwrap_or_default();
+
+        TitleFormat {
+            mode,
+            placeholder: module.str("placeholder").unwrap_or("Desktop").to_string(),
+            rules,
+        }
+    }
+
+    pub fn format(&self, window: &ActiveWindowInfo) -> String {
+        if window.class.is_empty() && window.title.is_empty() {
+            return self.placeholder.clone();
+        }
+
+        let mut text = match self.mode {
+            TitleMode::Class => window.class.clone(),
+            TitleMode::Title => window.title.clone(),
+            TitleMode::Both if window.title.is_empty() => window.class.clone(),
+            TitleMode::Both => format!("{} — {}", window.class, window.title),
+        };
+
+        for rule in &self.rules {
+            if rule
+                .class
+                .as_ref()
+                .is_none_or(|c| c.is_match(&window.class))
+            {
+                text = rule
+                    .pattern
+                    .replace_all(&text, rule.replacement.as_str())
+                    .into_owned();
+            }
+        }
+
+        if text.is_empty() {
+            self.placeholder.clone()
+        } else {
+            text
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title_format(options: &str) -> TitleFormat {
+        TitleFormat::from_config(&ModuleConfig {
+            name: "active_window".to_string(),
+            options: options.parse().unwrap(),
+        })
+    }
+
+    fn window(class: &str, title: &str) -> ActiveWindowInfo {
+        ActiveWindowInfo {
+            class: class.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn shows_the_placeholder_without_a_window() {
+        assert_eq!(title_format("").format(&window("", "")), "Desktop");
+        assert_eq!(
+            title_format(r#"placeholder = "~""#).format(&window("", "")),
+            "~"
+        );
+    }
+
+    #[test]
+    fn follows_the_show_mode() {
+        let w = window("kitty", "vim notes.md");
+        assert_eq!(title_format("").format(&w), "kitty");
+        assert_eq!(title_format(r#"show = "title""#).format(&w), "vim notes.md");
+        assert_eq!(
+            title_format(r#"show = "both""#).format(&w),
+            "kitty — vim notes.md"
+        );
+        assert_eq!(
+            title_format(r#"show = "both""#).format(&window("kitty", "")),
+            "kitty"
+        );
+    }
+
+    #[test]
+    fn applies_rewrite_rules_in_order() {
+        let format = title_format(
+            r#"
+            show = "title"
+
+            [[rewrite]]
+            class = "firefox"
+            match = " — Mozilla Firefox$"
+            replace = ""
+
+            [[rewrite]]
+            match = "^(.*)$"
+            replace = "[$1]"
+            "#,
+        );
+        assert_eq!(
+            format.format(&window("firefox", "Inbox — Mozilla Firefox")),
+            "[Inbox]"
+        );
+        // The first rule only applies to Firefox.
+        assert_eq!(
+            format.format(&window("kitty", "a — Mozilla Firefox")),
+            "[a — Mozilla Firefox]"
+        );
+    }
+
+    #[test]
+    fn falls_back_when_a_rule_empties_the_text() {
+        let format = title_format(
+            r#"
+            [[rewrite]]
+            match = ".*"
+            replace = ""
+            "#,
+        );
+        assert_eq!(format.format(&window("kitty", "")), "Desktop");
+    }
+
+    #[test]
+    fn skips_invalid_rules() {
+        let format = title_format(
+            r#"
+            [[rewrite]]
+            match = "("
+
+            [[rewrite]]
+            match = "kitty"
+            replace = "term"
+            "#,
+        );
+        assert_eq!(format.rules.len(), 1);
+        assert_eq!(format.format(&window("kitty", "")), "term");
+    }
+}
@@ -0,0 +1,209 @@
+use crate::window_manager::{self, Action, WorkspaceInfo};
+use gtk4::prelude::*;
+use gtk4::{
+    Box as GBox, EventControllerScroll, EventControllerScrollFlags, GestureClick, Label,
+    Orientation,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+fn set_class(widget: &impl IsA<gtk4::Widget>, class: &str, enabled: bool) {
+    if enabled {
+        widget.add_css_class(class);
+    } else {
+        widget.remove_css_class(class);
+    }
+}
+
+// One entry in the indicator. `action` is what gets dispatched on click and
+// doubles as the key for reusing the label across updates.
+struct Slot {
+    id: i32,
+    text: String,
+    action: Action,
+    special: bool,
+}
+
+// A panel bound to one of several monitors shows only that monitor's
+// workspaces; slots padded up to `min_count` would belong to the others.
+fn numbered_ids(info: &WorkspaceInfo, min_count: i32, per_monitor: bool) -> Vec<i32> {
+    if !per_monitor {
+        let max_ws = info.workspaces.iter().map(|ws| ws.id).max().unwrap_or(0);
+        return (1..=max_ws.max(min_count)).collect();
+    }
+
+    let mut ids: Vec<i32> = info
+        .workspaces
+        .iter()
+        .map(|ws| ws.id)
+        .chain([info.active])
+        .filter(|id| *id >= 1)
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
+}
+
+fn build_slots(info: &WorkspaceInfo, min_count: i32, per_monitor: bool) -> Vec<Slot> {
+    let mut slots: Vec<Slot> = numbered_ids(info, min_count, per_monitor)
+        .into_iter()
+        .map(|id| {
+            // Numbered workspaces can still carry a name from a workspace rule.
+            let text = info
+                .workspaces
+                .iter()
+                .find(|ws| ws.id == id && !ws.name.is_empty())
+                .map_or_else(|| id.to_string(), |ws| ws.name.clone());
+            Slot {
+                id,
+                text,
+                action: Action::Workspace(id),
+                special: false,
+            }
+        })
+        .collect();
+
+    // Named workspaces get negative ids from Hyprland.
+    slots.extend(
+        info.workspaces
+            .iter()
+            .filter(|ws| ws.id < 1)
+            .map(|ws| Slot {
+                id: ws.id,
+                text: ws.name.clone(),
+                action: Action::NamedWorkspace(ws.name.clone()),
+                special: false,
+            }),
+    );
+
+    if let Some(ws) = &info.special {
+        let (text, action) = if ws.name.is_empty() {
+            ("✦".to_string(), Action::ToggleSpecial(None))
+        } else {
+            (
+                format!("✦ {}", ws.name),
+                Action::ToggleSpecial(Some(ws.name.clone())),
+            )
+        };
+        slots.push(Slot {
+            id: ws.id,
+            text,
+            action,
+            special: true,
+        });
+    }
+
+    slots
+}
+
+fn build_workspace_label(action: &Action) -> Label {
+    let label = Label::new(None);
+    label.add_css_class("workspace");
+
+    let action = action.clone();
+    let click = GestureClick::new();
+    click.set_button(gtk4::gdk::BUTTON_PRIMARY);
+    click.connect_released(move |gesture, _n_press, _x, _y| {
+        gesture.set_state(gtk4::EventSequenceState::Claimed);
+        window_manager::spawn_dispatch(None, action.clone());
+    });
+    label.add_controller(click);
+
+    label
+}
+
+pub fn build_workspace_widget(
+    orientation: Orientation,
+    min_count: i32,
+    monitor: Option<String>,
+) -> (GBox, impl Fn(WorkspaceInfo)) {
+    let bound = monitor.is_some();
+    let spacing = match orientation {
+        Orientation::Vertical => 0,
+        _ => 4,
+    };
+    let container = GBox::new(orientation, spacing);
+    container.add_css_class("workspaces");
+    container.set_halign(gtk4::Align::Center);
+    container.set_valign(gtk4::Align::Center);
+
+    let scroll = EventControllerScroll::new(
+        EventControllerScrollFlags::BOTH_AXES | EventControllerScrollFlags::DISCRETE,
+    );
+    {
+        let monitor = monitor.clone();
+        scroll.connect_scroll(move |_controller, dx, dy| {
+            let delta = if dy != 0.0 { dy } else { dx };
+            if delta > 0.0 {
+                window_manager::spawn_dispatch(monitor.clone(), Action::RelativeWorkspace(1));
+            } else if delta < 0.0 {
+                window_manager::spawn_dispatch(monitor.clone(), Action::RelativeWorkspace(-1));
+            }
+            gtk4::glib::Propagation::Stop
+        });
+    }
+    container.add_controller(scroll);
+
+    let middle_click = GestureClick::new();
+    middle_click.set_button(gtk4::gdk::BUTTON_MIDDLE);
+    middle_click.connect_released(move |_gesture, _n_press, _x, _y| {
+        window_manager::spawn_dispatch(monitor.clone(), Action::ToggleSpecial(None));
+    });
+    container.add_controller(middle_click);
+
+    let labels: Rc<RefCell<HashMap<Action, Label>>> = Rc::new(RefCell::new(HashMap::new()));
+
+    // The caller's update loop owns this closure, so a strong reference here
+    // would keep the widget alive after it's removed from the panel.
+    let container_weak = container.downgrade();
+    let updater = move |info: WorkspaceInfo| {
+        let Some(container) = container_weak.upgrade() else {
+            return;
+        };
+        // Checked on every update as monitors come and go. With just one,
+        // every workspace is on it and padding is safe.
+        let per_monitor = bound && container.display().monitors().n_items() > 1;
+        let slots = build_slots(&info, min_count, per_monitor);
+        let mut labels = labels.borrow_mut();
+
+        // Keep the labels of workspaces that are still shown so their CSS
+        // transitions play, and drop the rest.
+        labels.retain(|action, label| {
+            let keep = slots.iter().any(|slot| &slot.action == action);
+            if !keep {
+                container.remove(label);
+            }
+            keep
+        });
+
+        let mut previous: Option<Label> = None;
+        for slot in &slots {
+            let label = labels
+                .entry(slot.action.clone())
+                .or_insert_with(|| {
+                    let label = build_workspace_label(&slot.action);
+                    container.append(&label);
+                    label
+                })
+                .clone();
+            container.reorder_child_after(&label, previous.as_ref());
+
+            let occupied = info
+                .workspaces
+                .iter()
+                .chain(info.special.iter())
+                .any(|ws| ws.id == slot.id && ws.windows > 0);
+            label.set_label(&slot.text);
+            set_class(&label, "active", slot.id == info.active || slot.special);
+            set_class(&label, "occupied", occupied);
+            set_class(&label, "empty", !occupied);
+            set_class(&label, "urgent", info.urgent.contains(&slot.id));
+            set_class(&label, "special", slot.special);
+
+            previous = Some(label);
+        }
+    };
+
+    (container, updater)
+}